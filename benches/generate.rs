@@ -1,20 +1,48 @@
+use std::alloc::{GlobalAlloc, Layout, System};
 use std::str::FromStr;
+use std::sync::atomic::{AtomicUsize, Ordering};
 
 use criterion::{criterion_group, criterion_main, BatchSize, Criterion};
 use rand::rngs::ThreadRng;
 use rand::seq::{IteratorRandom, SliceRandom};
 use rand::{thread_rng, Rng};
 
-use codice_fiscale::{CFString, CodiceFiscale, Gender, NaiveDate, Subject, ACTIVE_PLACES};
+use codice_fiscale::{
+    BirthPlace, CFString, CodiceFiscale, Gender, NaiveDate, Subject, ACTIVE_PLACES,
+};
+
+/// Counts the allocations so the encoding can be checked to never allocate
+struct CountingAllocator;
+
+static ALLOCATIONS: AtomicUsize = AtomicUsize::new(0);
+
+unsafe impl GlobalAlloc for CountingAllocator {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        ALLOCATIONS.fetch_add(1, Ordering::Relaxed);
+        System.alloc(layout)
+    }
+
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        System.dealloc(ptr, layout)
+    }
+}
+
+#[global_allocator]
+static GLOBAL: CountingAllocator = CountingAllocator;
+
 const GENDERS: [Gender; 2] = [Gender::Male, Gender::Female];
 const ALLOWED_CHARS: [char; 27] = [
     'A', 'B', 'C', 'D', 'E', 'F', 'G', 'H', 'I', 'J', 'K', 'L', 'M', 'N', 'O', 'P', 'Q', 'R', 'S',
     'T', 'U', 'V', 'W', 'X', 'Y', 'Z', ' ',
 ];
 
+/// Starts with a letter since names made only of spaces are rejected
 fn random_name(rng: &mut ThreadRng) -> String {
-    let length = rng.gen_range(1..20);
-    ALLOWED_CHARS.choose_multiple(rng, length).collect()
+    let length = rng.gen_range(0..19);
+    let first = ALLOWED_CHARS[..26].choose(rng).unwrap();
+    std::iter::once(first)
+        .chain(ALLOWED_CHARS.choose_multiple(rng, length))
+        .collect()
 }
 
 fn random_place(rng: &mut ThreadRng) -> (String, String) {
@@ -44,8 +72,10 @@ fn create_random_subject() -> Subject {
         last_name: CFString::new(random_name(&mut rng)).unwrap(),
         birth_date: random_date(&mut rng),
         gender: *GENDERS.choose(&mut rng).unwrap(),
-        birth_place: CFString::new(city).unwrap(),
-        birth_province: CFString::new(province).unwrap(),
+        birth_place: BirthPlace::Italy {
+            municipality: CFString::new(city).unwrap(),
+            province: Some(CFString::new(province).unwrap()),
+        },
     }
 }
 
@@ -61,10 +91,48 @@ fn bench_subjects(c: &mut Criterion) {
     });
 }
 
+fn bench_encode_allocations(c: &mut Criterion) {
+    let subjects = (0..1000)
+        .map(|_| create_random_subject())
+        .collect::<Vec<_>>();
+
+    let before = ALLOCATIONS.load(Ordering::Relaxed);
+    let codes = subjects
+        .iter()
+        .map(|subject| CodiceFiscale::encode(subject).unwrap())
+        .fold(0, |count, code| count + code.is_omocode() as usize);
+    let allocations = ALLOCATIONS.load(Ordering::Relaxed) - before;
+    assert_eq!(
+        allocations, 0,
+        "encoding {codes} codes allocated {allocations} times"
+    );
+
+    let subject = &subjects[0];
+    c.bench_function("encode without allocating", |b| {
+        b.iter(|| CodiceFiscale::encode(subject).unwrap())
+    });
+}
+
+fn bench_decode(c: &mut Criterion) {
+    c.bench_function("decode random codes", |b| {
+        b.iter_batched(
+            || CodiceFiscale::encode(&create_random_subject()).unwrap(),
+            |cf| cf.decode().unwrap_or_else(|e| panic!("{e:?} - {cf:?}")),
+            BatchSize::SmallInput,
+        )
+    });
+}
+
 fn bench_omocodes(c: &mut Criterion) {
     let cf = CodiceFiscale::from_str("CCCFBA85D03L219P").unwrap();
-    c.bench_function("all omocodes", |b| b.iter(|| cf.all_omocodes()));
+    c.bench_function("all omocodes", |b| b.iter(|| cf.all_omocodes().unwrap()));
 }
 
-criterion_group!(benches, bench_subjects, bench_omocodes);
+criterion_group!(
+    benches,
+    bench_subjects,
+    bench_encode_allocations,
+    bench_decode,
+    bench_omocodes
+);
 criterion_main!(benches);
@@ -1,4 +1,10 @@
-use std::{collections::HashMap, env, fs::File, io::Write, path::Path};
+use std::{
+    collections::{HashMap, HashSet},
+    env,
+    fs::File,
+    io::Write,
+    path::Path,
+};
 
 fn main() {
     let path = Path::new(&env::var("OUT_DIR").unwrap()).join("codegen.rs");
@@ -26,6 +32,215 @@ fn main() {
         inactive_map.build()
     )
     .expect("could not write inactive places map to file");
+
+    let mut records = Vec::new();
+    add_records(active_places, true, &mut records);
+    add_records(inactive_places, false, &mut records);
+
+    let mut by_name: HashMap<String, Vec<&Record>> = HashMap::new();
+    for record in &records {
+        let key = format!("{},{}", record.name, record.province);
+        by_name.entry(key).or_default().push(record);
+    }
+
+    let max_key_len = by_name.keys().map(String::len).max().unwrap_or_default();
+    writeln!(
+        &mut file,
+        "pub(crate) const MAX_NAME_KEY_LEN: usize = {max_key_len};"
+    )
+    .expect("could not write the maximum key length to file");
+
+    let mut by_name_map = phf_codegen::Map::new();
+    for (key, mut places) in by_name {
+        sort_records(&mut places);
+        by_name_map.entry(key, &records_to_slice(&places));
+    }
+
+    writeln!(
+        &mut file,
+        "pub(crate) static PLACES_BY_NAME: phf::Map<&'static str, &'static [Place]> = {};",
+        by_name_map.build()
+    )
+    .expect("could not write places by name map to file");
+
+    let mut by_municipality: HashMap<&str, Vec<&Record>> = HashMap::new();
+    for record in records.iter().filter(|r| r.province != "EE") {
+        by_municipality.entry(record.name).or_default().push(record);
+    }
+
+    let mut by_municipality_map = phf_codegen::Map::new();
+    for (name, mut places) in by_municipality {
+        sort_records(&mut places);
+        by_municipality_map.entry(name, &records_to_slice(&places));
+    }
+
+    writeln!(
+        &mut file,
+        "pub(crate) static PLACES_BY_MUNICIPALITY: phf::Map<&'static str, &'static [Place]> = {};",
+        by_municipality_map.build()
+    )
+    .expect("could not write places by municipality map to file");
+
+    let mut by_code: HashMap<&str, Vec<&Record>> = HashMap::new();
+    for record in &records {
+        by_code.entry(record.code).or_default().push(record);
+    }
+
+    let mut by_code_map = phf_codegen::Map::new();
+    for (code, mut places) in by_code {
+        sort_records(&mut places);
+        by_code_map.entry(code, &records_to_slice(&places));
+    }
+
+    writeln!(
+        &mut file,
+        "pub(crate) static PLACES_BY_CODE: phf::Map<&'static str, &'static [Place]> = {};",
+        by_code_map.build()
+    )
+    .expect("could not write places by code map to file");
+
+    write_countries(&records);
+    write_provinces();
+
+    #[cfg(feature = "ffi")]
+    write_header();
+}
+
+/// Generates the C header of the `ffi` module in `OUT_DIR`, a test of the module checks that
+/// `include/codice_fiscale.h` matches it and updates it when asked
+#[cfg(feature = "ffi")]
+fn write_header() {
+    let root = Path::new(env!("CARGO_MANIFEST_DIR"));
+    println!("cargo:rerun-if-changed=src/ffi.rs");
+
+    let config = cbindgen::Config {
+        language: cbindgen::Language::C,
+        header: Some(
+            "/* Generated from src/ffi.rs, update it with `UPDATE_HEADER=1 cargo test --features ffi` */"
+                .into(),
+        ),
+        include_guard: Some("CODICE_FISCALE_H".into()),
+        cpp_compat: true,
+        usize_is_size_t: true,
+        style: cbindgen::Style::Both,
+        enumeration: cbindgen::EnumConfig {
+            rename_variants: cbindgen::RenameRule::QualifiedScreamingSnakeCase,
+            ..Default::default()
+        },
+        ..Default::default()
+    };
+
+    cbindgen::Builder::new()
+        .with_config(config)
+        .with_src(root.join("src/ffi.rs"))
+        .generate()
+        .expect("could not generate the C header")
+        .write_to_file(Path::new(&env::var("OUT_DIR").unwrap()).join("codice_fiscale.h"));
+}
+
+fn write_countries(records: &[Record]) {
+    let path = Path::new(&env::var("OUT_DIR").unwrap()).join("countries.rs");
+    let mut file = File::create(path).expect("could not create countries.rs file");
+
+    let countries = include_str!("data/countries.csv");
+    let iso_codes = countries
+        .lines()
+        .map(|line| {
+            let mut fields = line.split(',');
+            let mut next_field = || fields.next().expect("missing field in countries csv file");
+            (next_field(), (next_field(), next_field()))
+        })
+        .collect::<HashMap<_, _>>();
+
+    let mut by_code: HashMap<&str, Vec<&Record>> = HashMap::new();
+    for record in records.iter().filter(|r| r.province == "EE") {
+        by_code.entry(record.code).or_default().push(record);
+    }
+
+    let mut by_code_map = phf_codegen::Map::new();
+    let mut by_iso_map = phf_codegen::Map::new();
+    for (code, mut places) in by_code {
+        sort_records(&mut places);
+        let iso = iso_codes.get(code);
+
+        let (alpha2, alpha3) = match iso {
+            Some((alpha2, alpha3)) => {
+                by_iso_map.entry(*alpha2, &format!(r#""{code}""#));
+                by_iso_map.entry(*alpha3, &format!(r#""{code}""#));
+                (
+                    format!(r#"Some("{alpha2}")"#),
+                    format!(r#"Some("{alpha3}")"#),
+                )
+            }
+            None => ("None".to_owned(), "None".to_owned()),
+        };
+
+        by_code_map.entry(
+            code,
+            &format!(
+                r#"Country {{ code: "{code}", name: "{}", iso_alpha2: {alpha2}, iso_alpha3: {alpha3}, continent: Continent::from_code("{code}") }}"#,
+                places[0].name,
+            ),
+        );
+    }
+
+    writeln!(
+        &mut file,
+        "static COUNTRIES_BY_CODE: phf::Map<&'static str, Country> = {};",
+        by_code_map.build()
+    )
+    .expect("could not write countries by code map to file");
+
+    writeln!(
+        &mut file,
+        "static COUNTRIES_BY_ISO: phf::Map<&'static str, &'static str> = {};",
+        by_iso_map.build()
+    )
+    .expect("could not write countries by iso code map to file");
+}
+
+/// Parses `data/provinces.csv`, with the format `code,name,region,active,successor`
+fn write_provinces() {
+    let path = Path::new(&env::var("OUT_DIR").unwrap()).join("provinces.rs");
+    let mut file = File::create(path).expect("could not create provinces.rs file");
+
+    let mut by_code_map = phf_codegen::Map::new();
+    for line in include_str!("data/provinces.csv").lines() {
+        let mut fields = line.split(',');
+        let mut next_field = || fields.next().expect("missing field in provinces csv file");
+        let (code, name, region, active, successor) = (
+            next_field(),
+            next_field(),
+            next_field(),
+            next_field(),
+            next_field(),
+        );
+
+        // "Friuli-Venezia Giulia" is the variant `Region::FriuliVeneziaGiulia`
+        let variant = region
+            .split(|c: char| !c.is_alphabetic())
+            .filter(|word| !word.is_empty())
+            .map(|word| word[..1].to_uppercase() + &word[1..])
+            .collect::<String>();
+        let successor = match successor {
+            "" => "None".to_owned(),
+            successor => format!(r#"Some("{successor}")"#),
+        };
+
+        by_code_map.entry(
+            code,
+            &format!(
+                r#"Province {{ code: "{code}", name: "{name}", region: Region::{variant}, active: {active}, successor: {successor} }}"#,
+            ),
+        );
+    }
+
+    writeln!(
+        &mut file,
+        "static PROVINCES_BY_CODE: phf::Map<&'static str, Province> = {};",
+        by_code_map.build()
+    )
+    .expect("could not write provinces by code map to file");
 }
 
 fn add_entries(places: &'static str, map: &mut phf_codegen::Map<&'static str>) {
@@ -41,5 +256,92 @@ fn parse_entry(line: &str) -> (&str, &str) {
         .split_once(',')
         .expect("could not find comma in csv file");
 
-    (rest, code)
+    // Only the name and province make up the key, validity dates are not part of it
+    let key_end = rest
+        .match_indices(',')
+        .nth(1)
+        .map_or(rest.len(), |(i, _)| i);
+
+    (&rest[..key_end], code)
+}
+
+struct Record {
+    code: &'static str,
+    name: &'static str,
+    province: &'static str,
+    active: bool,
+    valid_from: Option<&'static str>,
+    valid_to: Option<&'static str>,
+}
+
+/// Parses every line of a places csv file with the format
+/// `code,name,province[,valid_from,valid_to]` where the dates are in the `YYYY-MM-DD`
+/// format and can be left empty when they're not known
+fn add_records(places: &'static str, active: bool, records: &mut Vec<Record>) {
+    let mut seen = HashSet::new();
+
+    for line in places.lines() {
+        let mut fields = line.split(',');
+        let mut next_field = || fields.next().filter(|f| !f.is_empty());
+
+        let record = Record {
+            code: next_field().expect("could not find code in csv file"),
+            name: next_field().expect("could not find name in csv file"),
+            province: next_field().expect("could not find province in csv file"),
+            active,
+            valid_from: next_field(),
+            valid_to: next_field(),
+        };
+
+        if seen.insert(line) {
+            records.push(record);
+        }
+    }
+}
+
+/// Active places come first, then the most recent ones
+fn sort_records(records: &mut [&Record]) {
+    records.sort_by(|a, b| {
+        b.active
+            .cmp(&a.active)
+            .then(b.valid_from.cmp(&a.valid_from))
+            .then(a.code.cmp(b.code))
+            .then(a.name.cmp(b.name))
+    });
+}
+
+fn records_to_slice(records: &[&Record]) -> String {
+    let places = records
+        .iter()
+        .map(|r| {
+            format!(
+                r#"Place {{ code: "{}", name: "{}", province: "{}", active: {}, valid_from: {}, valid_to: {} }}"#,
+                r.code,
+                r.name,
+                r.province,
+                r.active,
+                date_to_expr(r.valid_from),
+                date_to_expr(r.valid_to),
+            )
+        })
+        .collect::<Vec<_>>()
+        .join(", ");
+
+    format!("&[{places}]")
+}
+
+fn date_to_expr(date: Option<&str>) -> String {
+    let Some(date) = date else {
+        return "None".to_owned();
+    };
+
+    let mut parts = date.split('-').map(|p| {
+        p.parse::<u32>()
+            .unwrap_or_else(|_| panic!("invalid date {date} in csv file"))
+    });
+    let (Some(year), Some(month), Some(day)) = (parts.next(), parts.next(), parts.next()) else {
+        panic!("invalid date {date} in csv file");
+    };
+
+    format!("NaiveDate::from_ymd_opt({year}, {month}, {day})")
 }
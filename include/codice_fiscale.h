@@ -0,0 +1,198 @@
+/* Generated from src/ffi.rs, update it with `UPDATE_HEADER=1 cargo test --features ffi` */
+
+#ifndef CODICE_FISCALE_H
+#define CODICE_FISCALE_H
+
+#include <stdarg.h>
+#include <stdbool.h>
+#include <stddef.h>
+#include <stdint.h>
+#include <stdlib.h>
+
+/**
+ * Size of the buffers that receive a codice fiscale, 16 characters and the terminating NUL
+ */
+#define CF_CODE_SIZE 17
+
+/**
+ * The outcome of a call. The values are stable, new ones are only ever added at the end
+ */
+typedef enum CfStatus {
+  CF_STATUS_OK = 0,
+  /**
+   * A required pointer argument was NULL
+   */
+  CF_STATUS_NULL_POINTER = 1,
+  /**
+   * A string argument was not valid UTF-8
+   */
+  CF_STATUS_INVALID_UTF8 = 2,
+  CF_STATUS_INCORRECT_LENGTH = 3,
+  CF_STATUS_INVALID_CHARACTER = 4,
+  CF_STATUS_INVALID_CHECKSUM = 5,
+  CF_STATUS_INVALID_DATE = 6,
+  CF_STATUS_INVALID_DAY = 7,
+  CF_STATUS_INVALID_MONTH_LETTER = 8,
+  CF_STATUS_INVALID_OMOCODE_LETTER = 9,
+  CF_STATUS_NON_ALPHANUMERIC = 10,
+  CF_STATUS_NON_NUMERIC = 11,
+  CF_STATUS_NOT_A_NATURAL_PERSON = 12,
+  CF_STATUS_UNKNOWN_PLACE_CODE = 13,
+  CF_STATUS_BELFIORE_CODE_NOT_FOUND = 14,
+  CF_STATUS_INCORRECT_CHECKSUM_INPUT_LENGTH = 15,
+  CF_STATUS_NON_NUMERIC_CHECKSUM_INPUT = 16,
+  CF_STATUS_NUMBER_OUT_OF_RANGE = 17,
+  /**
+   * A name, municipality, province or country with characters that aren't allowed
+   */
+  CF_STATUS_INVALID_NAME = 18,
+  /**
+   * The gender was neither 'M' nor 'F'
+   */
+  CF_STATUS_INVALID_GENDER = 19,
+  /**
+   * The birth date or the reference date doesn't exist
+   */
+  CF_STATUS_INVALID_INPUT_DATE = 20,
+  /**
+   * The province is NULL and different municipalities have the same name
+   */
+  CF_STATUS_AMBIGUOUS_MUNICIPALITY = 21,
+  /**
+   * The province is not a current or historic Italian province
+   */
+  CF_STATUS_UNKNOWN_PROVINCE = 22,
+} CfStatus;
+
+typedef enum CfPlaceStatus {
+  CF_PLACE_STATUS_ACTIVE,
+  CF_PLACE_STATUS_INACTIVE,
+  CF_PLACE_STATUS_FOREIGN,
+} CfPlaceStatus;
+
+typedef struct CfDate {
+  int32_t year;
+  /**
+   * From 1 to 12
+   */
+  uint32_t month;
+  uint32_t day;
+} CfDate;
+
+/**
+ * The data contained in a codice fiscale, the strings are NUL terminated
+ */
+typedef struct CfDecoded {
+  char last_name[4];
+  char first_name[4];
+  struct CfDate birth_date;
+  /**
+   * 'M' or 'F'
+   */
+  char gender;
+  char belfiore_code[5];
+  /**
+   * "EE" for people born abroad
+   */
+  char province[3];
+  /**
+   * Name of the municipality or the country, owned by the library
+   */
+  char *birth_place;
+  enum CfPlaceStatus place_status;
+  bool is_omocode;
+} CfDecoded;
+
+/**
+ * The data to encode. People born in Italy have a municipality, a province that can be NULL
+ * when the name is unambiguous, and a NULL country. People born abroad have a country, either
+ * its name or its ISO code
+ */
+typedef struct CfSubject {
+  const char *first_name;
+  const char *last_name;
+  struct CfDate birth_date;
+  /**
+   * 'M' or 'F'
+   */
+  char gender;
+  const char *municipality;
+  const char *province;
+  const char *country;
+} CfSubject;
+
+#ifdef __cplusplus
+extern "C" {
+#endif // __cplusplus
+
+/**
+ * Returns a static NUL terminated description of a [`CfStatus`], or a generic message for
+ * values that are not a status
+ */
+const char *cf_status_message(int status);
+
+/**
+ * Checks every character, the date and the check character of a codice fiscale
+ *
+ * # Safety
+ *
+ * `code` must be NULL or point to a NUL terminated string
+ */
+enum CfStatus cf_validate(const char *code);
+
+/**
+ * Validates a codice fiscale like [`cf_validate`] and writes it in uppercase to `out`
+ *
+ * # Safety
+ *
+ * `code` must be NULL or point to a NUL terminated string, `out` must be NULL or point to
+ * [`CF_CODE_SIZE`] writable bytes
+ */
+enum CfStatus cf_parse(const char *code, char *out);
+
+/**
+ * Writes the check character of the first 15 characters of a code to `out`
+ *
+ * # Safety
+ *
+ * `partial` must be NULL or point to a NUL terminated string, `out` must be NULL or point to
+ * a writable byte
+ */
+enum CfStatus cf_compute_checksum(const char *partial, char *out);
+
+/**
+ * Decodes a codice fiscale. The century of the birth date is the most recent one that's not
+ * after `reference`, today if it's NULL. On success `out` must be released with
+ * [`cf_decoded_free`], on failure it's left untouched
+ *
+ * # Safety
+ *
+ * `code` must be NULL or point to a NUL terminated string, `reference` must be NULL or point
+ * to a date and `out` must be NULL or point to a writable [`CfDecoded`]
+ */
+enum CfStatus cf_decode(const char *code, const struct CfDate *reference, struct CfDecoded *out);
+
+/**
+ * Releases the memory owned by a [`CfDecoded`], calling it again on the same struct is a no-op
+ *
+ * # Safety
+ *
+ * `decoded` must be NULL or point to a [`CfDecoded`] filled by [`cf_decode`]
+ */
+void cf_decoded_free(struct CfDecoded *decoded);
+
+/**
+ * Encodes the codice fiscale of a subject and writes it to `out`
+ *
+ * # Safety
+ *
+ * `subject` must be NULL or point to a [`CfSubject`] whose strings are NULL or NUL
+ * terminated, `out` must be NULL or point to [`CF_CODE_SIZE`] writable bytes
+ */
+enum CfStatus cf_encode(const struct CfSubject *subject, char *out);
+
+#ifdef __cplusplus
+}  // extern "C"
+#endif  // __cplusplus
+
+#endif  /* CODICE_FISCALE_H */
@@ -0,0 +1,474 @@
+use std::io::{Read, Write};
+
+use chrono::{NaiveDate, Utc};
+use csv::{ReaderBuilder, StringRecord, WriterBuilder};
+
+use crate::{
+    BirthPlace, CFStringError, CenturyPolicy, CodiceFiscale, DecodedBirthPlace, DecodedData,
+    Gender, Subject,
+};
+
+/// Columns appended to every row of the enriched CSV
+pub const OUTPUT_COLUMNS: [&str; 9] = [
+    "valid",
+    "error",
+    "decoded_birth_date",
+    "decoded_gender",
+    "decoded_birth_place",
+    "decoded_province",
+    "belfiore_code",
+    "verified",
+    "mismatches",
+];
+
+/// Input columns holding the claimed data of the subject, rows are only verified when the
+/// names, the birth date, the gender and either the place or the country are there. The province
+/// is optional when no other municipality has ever had the same name
+const FIRST_NAME: &str = "first_name";
+const LAST_NAME: &str = "last_name";
+const BIRTH_DATE: &str = "birth_date";
+const GENDER: &str = "gender";
+const BIRTH_PLACE: &str = "birth_place";
+const PROVINCE: &str = "province";
+const COUNTRY: &str = "country";
+
+#[derive(Debug, Clone)]
+pub struct BatchOptions {
+    /// Name of the column containing the codice fiscale
+    pub code_column: String,
+    pub delimiter: u8,
+    /// Used to resolve the birth century, see [`CodiceFiscale::decode_with`]
+    pub reference_date: NaiveDate,
+}
+
+impl Default for BatchOptions {
+    fn default() -> Self {
+        Self {
+            code_column: "codice_fiscale".to_owned(),
+            delimiter: b',',
+            reference_date: Utc::now().date_naive(),
+        }
+    }
+}
+
+/// Row counts of a processed batch, the header is not counted
+#[derive(Debug, Default, PartialEq, Eq, Clone, Copy)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct BatchSummary {
+    pub rows: usize,
+    /// Rows whose code could not be parsed or decoded
+    pub invalid: usize,
+    /// Rows with a valid code that doesn't match the claimed data or whose data is malformed
+    pub mismatched: usize,
+}
+
+#[derive(Debug)]
+pub enum BatchError {
+    Csv(csv::Error),
+    MissingColumn(String),
+}
+
+impl std::error::Error for BatchError {}
+impl std::fmt::Display for BatchError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Csv(error) => write!(f, "{error}"),
+            Self::MissingColumn(column) => write!(f, "the input has no {column} column"),
+        }
+    }
+}
+
+impl From<csv::Error> for BatchError {
+    fn from(error: csv::Error) -> Self {
+        Self::Csv(error)
+    }
+}
+
+impl From<std::io::Error> for BatchError {
+    fn from(error: std::io::Error) -> Self {
+        Self::Csv(error.into())
+    }
+}
+
+/// Reads a CSV with a header from `input` and writes it to `output` with the [`OUTPUT_COLUMNS`]
+/// appended. Each row is parsed, decoded and, when the row has the subject's data, verified
+/// against it. Rows are processed one at a time so the input can be of any size. Rows with
+/// more or fewer fields than the header are reported as invalid and written with as many
+/// fields as the header so that the appended columns stay aligned, the values of the extra
+/// fields are listed in the error
+pub fn enrich<R: Read, W: Write>(
+    input: R,
+    output: W,
+    options: &BatchOptions,
+) -> Result<BatchSummary, BatchError> {
+    let mut reader = ReaderBuilder::new()
+        .delimiter(options.delimiter)
+        .flexible(true)
+        .from_reader(input);
+    let mut writer = WriterBuilder::new()
+        .delimiter(options.delimiter)
+        .from_writer(output);
+
+    let header = reader.headers()?.clone();
+    let columns = Columns::new(&header, &options.code_column)?;
+
+    let mut output_header = header.clone();
+    output_header.extend(OUTPUT_COLUMNS);
+    writer.write_record(&output_header)?;
+
+    let mut summary = BatchSummary::default();
+    let mut record = StringRecord::new();
+    while reader.read_record(&mut record)? {
+        let row = if record.len() == header.len() {
+            check_row(&record, &columns, options.reference_date)
+        } else {
+            let mut error = format!("expected {} fields, found {}", header.len(), record.len());
+            if record.len() > header.len() {
+                let dropped = record.iter().skip(header.len()).collect::<Vec<_>>();
+                error.push_str(&format!(", dropped {dropped:?}"));
+            }
+            record.truncate(header.len());
+            while record.len() < header.len() {
+                record.push_field("");
+            }
+            RowResult::invalid(error)
+        };
+
+        summary.rows += 1;
+        if row.decoded.is_none() {
+            summary.invalid += 1;
+        } else if row.verified == Some(false) {
+            summary.mismatched += 1;
+        }
+
+        record.extend(row.into_fields());
+        writer.write_record(&record)?;
+    }
+
+    writer.flush()?;
+    Ok(summary)
+}
+
+/// Indices of the input columns
+struct Columns {
+    code: usize,
+    first_name: Option<usize>,
+    last_name: Option<usize>,
+    birth_date: Option<usize>,
+    gender: Option<usize>,
+    birth_place: Option<usize>,
+    province: Option<usize>,
+    country: Option<usize>,
+}
+
+impl Columns {
+    fn new(header: &StringRecord, code_column: &str) -> Result<Self, BatchError> {
+        let find = |name: &str| header.iter().position(|column| column.trim() == name);
+
+        Ok(Self {
+            code: find(code_column).ok_or_else(|| BatchError::MissingColumn(code_column.into()))?,
+            first_name: find(FIRST_NAME),
+            last_name: find(LAST_NAME),
+            birth_date: find(BIRTH_DATE),
+            gender: find(GENDER),
+            birth_place: find(BIRTH_PLACE),
+            province: find(PROVINCE),
+            country: find(COUNTRY),
+        })
+    }
+
+    fn has_subject(&self) -> bool {
+        self.first_name.is_some()
+            && self.last_name.is_some()
+            && self.birth_date.is_some()
+            && self.gender.is_some()
+            && (self.birth_place.is_some() || self.country.is_some())
+    }
+}
+
+struct RowResult {
+    decoded: Option<DecodedData>,
+    error: Option<String>,
+    /// `None` when the row has no subject data to verify against
+    verified: Option<bool>,
+    mismatches: Vec<&'static str>,
+}
+
+impl RowResult {
+    fn invalid(error: String) -> Self {
+        Self {
+            decoded: None,
+            error: Some(error),
+            verified: None,
+            mismatches: Vec::new(),
+        }
+    }
+
+    fn into_fields(self) -> [String; OUTPUT_COLUMNS.len()] {
+        let valid = self.decoded.is_some();
+        let (birth_date, gender, place, province, belfiore_code) = match self.decoded {
+            Some(decoded) => {
+                let (place, province) = match decoded.birth_place {
+                    DecodedBirthPlace::Italy {
+                        municipality,
+                        province,
+                    } => (municipality, province),
+                    DecodedBirthPlace::Foreign(country) => {
+                        (country.display_name(), "EE".to_owned())
+                    }
+                };
+                let gender = match decoded.gender {
+                    Gender::Male => "M",
+                    Gender::Female => "F",
+                };
+                (
+                    decoded.birth_date.to_string(),
+                    gender.to_owned(),
+                    place,
+                    province,
+                    decoded.belfiore_code,
+                )
+            }
+            None => Default::default(),
+        };
+
+        [
+            valid.to_string(),
+            self.error.unwrap_or_default(),
+            birth_date,
+            gender,
+            place,
+            province,
+            belfiore_code,
+            self.verified.map(|v| v.to_string()).unwrap_or_default(),
+            self.mismatches.join(";"),
+        ]
+    }
+}
+
+fn check_row(record: &StringRecord, columns: &Columns, reference: NaiveDate) -> RowResult {
+    let code = record.get(columns.code).unwrap_or_default().trim();
+    let decoded = CodiceFiscale::parse_strict(code)
+        .and_then(|cf| Ok((cf.decode_with(reference, CenturyPolicy::PreferPast)?, cf)));
+    let (decoded, code) = match decoded {
+        Ok(decoded) => decoded,
+        Err(error) => return RowResult::invalid(error.to_string()),
+    };
+
+    let mut row = RowResult {
+        decoded: Some(decoded),
+        error: None,
+        verified: None,
+        mismatches: Vec::new(),
+    };
+
+    if !columns.has_subject() {
+        return row;
+    }
+
+    let subject = match parse_subject(record, columns) {
+        Ok(subject) => subject,
+        Err(error) => {
+            row.error = Some(error);
+            row.verified = Some(false);
+            return row;
+        }
+    };
+
+    let verification = match code.verify(&subject) {
+        Ok(verification) => verification,
+        Err(error) => {
+            row.error = Some(error.to_string());
+            row.verified = Some(false);
+            return row;
+        }
+    };
+
+    let checks = [
+        (LAST_NAME, &verification.last_name),
+        (FIRST_NAME, &verification.first_name),
+        (BIRTH_DATE, &verification.birth_date),
+        (GENDER, &verification.gender),
+        (BIRTH_PLACE, &verification.birth_place),
+    ];
+    row.mismatches = checks
+        .into_iter()
+        .filter(|(_, check)| !check.is_match())
+        .map(|(name, _)| name)
+        .collect();
+    row.verified = Some(row.mismatches.is_empty());
+
+    row
+}
+
+/// Builds the subject from the row, the error names the column that couldn't be parsed
+fn parse_subject(record: &StringRecord, columns: &Columns) -> Result<Subject, String> {
+    let field = |index: Option<usize>| {
+        index
+            .and_then(|i| record.get(i))
+            .map(str::trim)
+            .filter(|value| !value.is_empty())
+    };
+    let required = |index: Option<usize>, name: &str| {
+        field(index).ok_or_else(|| format!("{name}: missing value"))
+    };
+    let invalid = |name: &'static str| move |error: CFStringError| format!("{name}: {error}");
+
+    let first_name = required(columns.first_name, FIRST_NAME)?
+        .parse()
+        .map_err(invalid(FIRST_NAME))?;
+    let last_name = required(columns.last_name, LAST_NAME)?
+        .parse()
+        .map_err(invalid(LAST_NAME))?;
+    let birth_date = required(columns.birth_date, BIRTH_DATE)?
+        .parse::<NaiveDate>()
+        .map_err(|error| format!("{BIRTH_DATE}: {error}"))?;
+    let gender = match required(columns.gender, GENDER)?
+        .to_ascii_uppercase()
+        .as_str()
+    {
+        "M" | "MALE" => Gender::Male,
+        "F" | "FEMALE" => Gender::Female,
+        other => return Err(format!("{GENDER}: unknown gender {other}")),
+    };
+
+    let birth_place = match field(columns.country) {
+        Some(country) => BirthPlace::Foreign {
+            country: country.parse().map_err(invalid(COUNTRY))?,
+        },
+        None => BirthPlace::Italy {
+            municipality: required(columns.birth_place, BIRTH_PLACE)?
+                .parse()
+                .map_err(invalid(BIRTH_PLACE))?,
+            province: field(columns.province)
+                .map(str::parse)
+                .transpose()
+                .map_err(invalid(PROVINCE))?,
+        },
+    };
+
+    Subject::new(first_name, last_name, birth_date, gender, birth_place)
+        .map_err(|error| format!("{PROVINCE}: {error}"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn options() -> BatchOptions {
+        BatchOptions {
+            reference_date: NaiveDate::from_ymd_opt(2024, 1, 1).unwrap(),
+            ..Default::default()
+        }
+    }
+
+    fn run(input: &str) -> (BatchSummary, Vec<StringRecord>) {
+        let mut output = Vec::new();
+        let summary = enrich(input.as_bytes(), &mut output, &options()).unwrap();
+
+        let records = ReaderBuilder::new()
+            .from_reader(output.as_slice())
+            .records()
+            .collect::<Result<_, _>>()
+            .unwrap();
+        (summary, records)
+    }
+
+    #[test]
+    fn test_enrich_codes_only() {
+        let (summary, records) = run("id,codice_fiscale\n1,GLNGCR56P10G224Q\n2,GLNGCR56P10G224A\n");
+
+        assert_eq!(
+            summary,
+            BatchSummary {
+                rows: 2,
+                invalid: 1,
+                mismatched: 0
+            }
+        );
+        assert_eq!(
+            records[0].iter().collect::<Vec<_>>(),
+            [
+                "1",
+                "GLNGCR56P10G224Q",
+                "true",
+                "",
+                "1956-09-10",
+                "M",
+                "Padova",
+                "PD",
+                "G224",
+                "",
+                ""
+            ]
+        );
+        assert_eq!(&records[1][2], "false");
+        assert_eq!(
+            &records[1][3],
+            "the check character does not match the rest of the code"
+        );
+    }
+
+    #[test]
+    fn test_enrich_with_subject() {
+        let input = "\
+codice_fiscale,first_name,last_name,birth_date,gender,birth_place,province,country
+GLNGCR56P10G224Q,Giancarlo,Galan,1956-09-10,M,Padova,PD,
+GLNGCR56P10G224Q,Giovanni,Galan,1956-09-10,F,Padova,PD,
+GLNGCR56P10Z404L,Giancarlo,Galan,1956-09-10,M,,,US
+GLNGCR56P10G224Q,Giancarlo,Galan,10/09/1956,M,Padova,PD,
+GLNGCR56P10G224Q,Giancarlo,Galan,1956-09-10,M,Padova,,
+";
+        let (summary, records) = run(input);
+
+        assert_eq!(
+            summary,
+            BatchSummary {
+                rows: 5,
+                invalid: 0,
+                mismatched: 2
+            }
+        );
+
+        assert_eq!(&records[0][15], "true");
+        assert_eq!(&records[1][15], "false");
+        assert_eq!(&records[1][16], "first_name;gender");
+        assert_eq!(&records[2][15], "true");
+        assert_eq!(&records[2][13], "EE");
+        assert_eq!(&records[3][15], "false");
+        assert!(records[3][9].starts_with("birth_date:"));
+        assert_eq!(&records[4][15], "true");
+    }
+
+    #[test]
+    fn test_enrich_wrong_field_count() {
+        let input = "id,codice_fiscale\n1,GLNGCR56P10G224Q,extra\n2\n3,GLNGCR56P10G224Q\n";
+        let (summary, records) = run(input);
+
+        assert_eq!(
+            summary,
+            BatchSummary {
+                rows: 3,
+                invalid: 2,
+                mismatched: 0
+            }
+        );
+        assert_eq!(records[0].len(), 2 + OUTPUT_COLUMNS.len());
+        assert_eq!(&records[0][2], "false");
+        assert_eq!(
+            &records[0][3],
+            "expected 2 fields, found 3, dropped [\"extra\"]"
+        );
+        assert_eq!(records[1].len(), 2 + OUTPUT_COLUMNS.len());
+        assert_eq!(&records[1][3], "expected 2 fields, found 1");
+        assert_eq!(&records[2][2], "true");
+    }
+
+    #[test]
+    fn test_missing_code_column() {
+        let result = enrich("id,code\n1,X\n".as_bytes(), Vec::new(), &options());
+        assert!(
+            matches!(result, Err(BatchError::MissingColumn(column)) if column == "codice_fiscale")
+        );
+    }
+}
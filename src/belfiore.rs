@@ -0,0 +1,133 @@
+use std::str::FromStr;
+
+use crate::Place;
+
+#[derive(Debug, PartialEq, Eq)]
+pub enum BelfioreCodeError {
+    IncorrectLength(usize),
+    InvalidFormat,
+    Unknown,
+}
+
+impl std::error::Error for BelfioreCodeError {}
+impl std::fmt::Display for BelfioreCodeError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let message = match self {
+            Self::IncorrectLength(len) => {
+                return write!(f, "belfiore code must be 4 characters long, not {len}")
+            }
+            Self::InvalidFormat => "belfiore code must be a letter followed by 3 digits",
+            Self::Unknown => "belfiore code is not in the tables",
+        };
+        write!(f, "{message}")
+    }
+}
+
+/// The cadastral code of a municipality or foreign state, like "G224" or "Z404", that has
+/// been checked to exist in the tables
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct BelfioreCode([u8; 4]);
+
+impl BelfioreCode {
+    /// Accepts lowercase letters, the code must not contain omocode letters
+    pub fn new(code: &str) -> Result<Self, BelfioreCodeError> {
+        let bytes: [u8; 4] = code
+            .as_bytes()
+            .try_into()
+            .map_err(|_| BelfioreCodeError::IncorrectLength(code.len()))?;
+
+        let [letter, digits @ ..] = bytes.map(|b| b.to_ascii_uppercase());
+        if !letter.is_ascii_uppercase() || !digits.iter().all(u8::is_ascii_digit) {
+            return Err(BelfioreCodeError::InvalidFormat);
+        }
+
+        let code = Self([letter, digits[0], digits[1], digits[2]]);
+        if code.places().is_empty() {
+            return Err(BelfioreCodeError::Unknown);
+        }
+        Ok(code)
+    }
+
+    pub fn get(&self) -> &str {
+        std::str::from_utf8(&self.0).unwrap()
+    }
+
+    /// Every place that has ever used this code, see [`Place::by_code`]
+    pub fn places(&self) -> &'static [Place] {
+        Place::by_code(self.get())
+    }
+
+    /// Only the codes of foreign states start with Z
+    pub fn is_foreign(&self) -> bool {
+        self.0[0] == b'Z'
+    }
+}
+
+impl std::fmt::Display for BelfioreCode {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(self.get())
+    }
+}
+
+impl FromStr for BelfioreCode {
+    type Err = BelfioreCodeError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Self::new(s)
+    }
+}
+
+impl TryFrom<&str> for BelfioreCode {
+    type Error = BelfioreCodeError;
+
+    fn try_from(value: &str) -> Result<Self, Self::Error> {
+        Self::new(value)
+    }
+}
+
+#[cfg(feature = "serde")]
+impl serde::Serialize for BelfioreCode {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(self.get())
+    }
+}
+
+#[cfg(feature = "serde")]
+impl<'de> serde::Deserialize<'de> for BelfioreCode {
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let string = String::deserialize(deserializer)?;
+        Self::new(&string).map_err(serde::de::Error::custom)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_belfiore_code() {
+        let code = BelfioreCode::new("g224").unwrap();
+        assert_eq!(code.get(), "G224");
+        assert_eq!(code.places()[0].name, "padova");
+        assert!(!code.is_foreign());
+
+        assert!(BelfioreCode::new("Z404").unwrap().is_foreign());
+    }
+
+    #[test]
+    fn test_belfiore_code_errors() {
+        assert_eq!(
+            BelfioreCode::new("G22"),
+            Err(BelfioreCodeError::IncorrectLength(3))
+        );
+        assert_eq!(
+            BelfioreCode::new("G2NN"),
+            Err(BelfioreCodeError::InvalidFormat)
+        );
+        assert_eq!(
+            BelfioreCode::new("2224"),
+            Err(BelfioreCodeError::InvalidFormat)
+        );
+        assert_eq!(BelfioreCode::new("C008"), Err(BelfioreCodeError::Unknown));
+    }
+}
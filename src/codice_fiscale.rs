@@ -1,19 +1,22 @@
-use std::error::Error;
+use core::error::Error;
+use core::str::FromStr;
+#[cfg(feature = "std")]
 use std::sync::OnceLock;
 
 use chrono::Datelike;
 use chrono::NaiveDate;
+#[cfg(feature = "std")]
 use chrono::Utc;
 
-use crate::{all_subsets, CFString, DecodedData, Gender, Subject};
+#[cfg(feature = "std")]
+use crate::{
+    all_subsets, place::Place, string::SEPARATORS, BirthPlace, CFString, Country,
+    DecodedBirthPlace, DecodedData, PlaceStatus, Subject,
+};
+use crate::{CenturyPolicy, Gender};
 
-include!(concat!(env!("OUT_DIR"), "/codegen.rs"));
-
-const VOWELS: [char; 6] = ['A', 'E', 'I', 'O', 'U', ' '];
-const CONSONANTS: [char; 22] = [
-    'B', 'C', 'D', 'F', 'G', 'H', 'J', 'K', 'L', 'M', 'N', 'P', 'Q', 'R', 'S', 'T', 'V', 'W', 'X',
-    'Y', 'Z', ' ',
-];
+#[cfg(feature = "std")]
+const VOWELS: [u8; 5] = [b'A', b'E', b'I', b'O', b'U'];
 const MONTH_CODES: [char; 12] = ['A', 'B', 'C', 'D', 'E', 'H', 'L', 'M', 'P', 'R', 'S', 'T'];
 const CHECK_CODE_NUM_ODD: [usize; 10] = [1, 0, 5, 7, 9, 13, 15, 17, 19, 21];
 const CHECK_CODE_NUM_EVEN: [usize; 10] = [0, 1, 2, 3, 4, 5, 6, 7, 8, 9];
@@ -23,36 +26,98 @@ const CHECK_CODE_LET_ODD: [usize; 26] = [
 const CHECK_CODE_LET_EVEN: [usize; 26] = [
     0, 1, 2, 3, 4, 5, 6, 7, 8, 9, 10, 11, 12, 13, 14, 15, 16, 17, 18, 19, 20, 21, 22, 23, 24, 25,
 ];
+#[cfg(feature = "std")]
+pub(crate) const FOREIGN_PROVINCE: &str = "EE";
 const OMOCODE_POSITIONS: [usize; 7] = [6, 7, 9, 10, 12, 13, 14];
 const OMOCODE_LETTERS: [char; 10] = ['L', 'M', 'N', 'P', 'Q', 'R', 'S', 'T', 'U', 'V'];
+#[cfg(feature = "std")]
 static OMOCODE_SUBSETS: OnceLock<Vec<Vec<usize>>> = OnceLock::new();
 
-#[derive(Debug, Clone)]
-pub struct CodiceFiscale(String);
+/// Stored as its 16 ascii bytes so it can be copied around without allocating, letters are
+/// always uppercase
+#[derive(Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct CodiceFiscale([u8; 16]);
 
 impl CodiceFiscale {
+    #[cfg(feature = "std")]
     pub fn encode(subject: &Subject) -> Result<Self, GenerationError> {
         Self::try_from(subject)
     }
 
+    #[cfg(feature = "std")]
     pub fn decode(&self) -> Result<DecodedData, ValidationError> {
+        self.decode_with(Utc::now().date_naive(), CenturyPolicy::PreferPast)
+    }
+
+    /// Decodes the codice fiscale resolving the birth century relative to `reference`,
+    /// which makes the result independent of when it's run
+    #[cfg(feature = "std")]
+    pub fn decode_with(
+        &self,
+        reference: NaiveDate,
+        policy: CenturyPolicy,
+    ) -> Result<DecodedData, ValidationError> {
         let code = self.normalize()?;
         let code = code.get();
 
-        let (birth_date, gender) = Self::decode_date(code)?;
-        let (birth_place, birth_province) =
-            Self::decode_birth_place(code).ok_or(ValidationError::UnknownPlaceCode)?;
+        let (birth_date, gender, alternative_birth_date) =
+            Self::decode_date_with(code, reference, policy)?;
+
+        let belfiore_code = &code[11..15];
+        let places = Place::by_code(belfiore_code);
+        // The code is active if any of its records is, they come first
+        let active = places
+            .first()
+            .ok_or(ValidationError::UnknownPlaceCode)?
+            .active;
+        let place = Place::valid_on(places, birth_date).unwrap_or(&places[0]);
+
+        let (birth_place, place_status) = if place.province == FOREIGN_PROVINCE {
+            let country =
+                Country::by_code(belfiore_code).ok_or(ValidationError::UnknownPlaceCode)?;
+            (DecodedBirthPlace::Foreign(country), PlaceStatus::Foreign)
+        } else {
+            let birth_place = DecodedBirthPlace::Italy {
+                municipality: place.display_name(),
+                province: place.province.to_owned(),
+            };
+            let status = if active {
+                PlaceStatus::Active
+            } else {
+                PlaceStatus::Inactive
+            };
+            (birth_place, status)
+        };
 
         Ok(DecodedData {
+            last_name: code[0..3].to_owned(),
+            first_name: code[3..6].to_owned(),
             birth_date,
+            alternative_birth_date,
             gender,
             birth_place,
-            birth_province,
+            belfiore_code: belfiore_code.to_owned(),
+            place_status,
+            omocode_positions: self.omocode_positions(),
         })
     }
 
+    /// Returns every known place for the Belfiore code contained in the codice fiscale,
+    /// including historic names and name variants, see [`Place::by_code`]
+    #[cfg(feature = "std")]
+    pub fn birth_places(&self) -> Result<&'static [Place], ValidationError> {
+        let code = self.normalize()?;
+        let places = Place::by_code(&code.get()[11..15]);
+
+        if places.is_empty() {
+            return Err(ValidationError::UnknownPlaceCode);
+        }
+
+        Ok(places)
+    }
+
     pub fn normalize(&self) -> Result<Self, ValidationError> {
-        let mut bytes = self.0.as_bytes()[0..15].to_vec();
+        let mut bytes = self.0;
         for position in OMOCODE_POSITIONS {
             let current = bytes[position] as char;
             if current.is_ascii_digit() {
@@ -65,116 +130,262 @@ impl CodiceFiscale {
                 .ok_or(ValidationError::InvalidOmocodeLetter)?;
             bytes[position] = letter_index as u8 + b'0';
         }
+        bytes[15] = checksum(&bytes[0..15]);
+
+        Ok(CodiceFiscale(bytes))
+    }
+
+    /// Parses a codice fiscale checking the kind of character at every position, the month
+    /// letter, the day range and the check character. Lowercase letters are accepted
+    pub fn parse_strict(string: &str) -> Result<Self, ValidationError> {
+        let code = Self::from_str(string)?;
+        let bytes = &code.0;
+
+        for (position, &b) in bytes.iter().enumerate() {
+            let (valid, expected) = if OMOCODE_POSITIONS.contains(&position) {
+                let valid = b.is_ascii_digit() || OMOCODE_LETTERS.contains(&(b as char));
+                (valid, CharacterClass::DigitOrOmocodeLetter)
+            } else {
+                (b.is_ascii_alphabetic(), CharacterClass::Letter)
+            };
+
+            if !valid {
+                return Err(ValidationError::InvalidCharacter { position, expected });
+            }
+        }
+
+        if !MONTH_CODES.contains(&(bytes[8] as char)) {
+            return Err(ValidationError::InvalidMonthLetter);
+        }
 
-        let mut string = String::from_utf8(bytes).unwrap();
-        let checksum =
-            Self::compute_checksum(&string).map_err(|_| ValidationError::IncorrectLength(15))?;
-        string.push(checksum);
+        let normalized = code.normalize()?;
+        let day = &normalized.0[9..11];
+        let day = (day[0] - b'0') * 10 + (day[1] - b'0');
+        if !(1..=31).contains(&day) && !(41..=71).contains(&day) {
+            return Err(ValidationError::InvalidDay);
+        }
 
-        Ok(CodiceFiscale(string))
+        // Normalizing recomputes the check character so it can't be used for the comparison
+        if bytes[15] != checksum(&bytes[0..15]) {
+            return Err(ValidationError::InvalidChecksum);
+        }
+
+        Ok(code)
     }
 
     pub fn get(&self) -> &str {
+        core::str::from_utf8(&self.0).unwrap()
+    }
+
+    pub fn as_bytes(&self) -> &[u8; 16] {
         &self.0
     }
 
+    #[cfg(feature = "std")]
     pub fn encode_last_name(last_name: CFString<&str>) -> String {
-        let consonants = last_name.to_ascii_uppercase().replace(VOWELS, "");
-        let vowels = last_name.to_ascii_uppercase().replace(CONSONANTS, "");
-
-        format!("{consonants}{vowels}XXX")[..3].to_owned()
+        String::from_utf8(last_name_code(&last_name).to_vec()).unwrap()
     }
 
+    #[cfg(feature = "std")]
     pub fn encode_first_name(first_name: CFString<&str>) -> String {
-        let consonants = first_name.to_ascii_uppercase().replace(VOWELS, "");
-        let b = consonants.as_bytes();
-
-        if b.len() > 3 {
-            format!("{}{}{}", b[0] as char, b[2] as char, b[3] as char)
-        } else {
-            let vowels = first_name.to_ascii_uppercase().replace(CONSONANTS, "");
-            format!("{consonants}{vowels}XXX")[..3].to_owned()
-        }
+        String::from_utf8(first_name_code(&first_name).to_vec()).unwrap()
     }
 
+    #[cfg(feature = "std")]
     pub fn encode_birth_date(birth_date: NaiveDate, gender: Gender) -> String {
-        let year = birth_date.year();
-        let month = MONTH_CODES[birth_date.month0() as usize];
-        let mut day = birth_date.day();
-
-        if gender == Gender::Female {
-            day += 40;
-        }
-
-        format!("{year:02}{month}{day:02}", year = year % 100)
+        String::from_utf8(birth_date_code(birth_date, gender).to_vec()).unwrap()
     }
 
-    pub fn encode_birth_place(city: CFString<&str>, province: CFString<&str>) -> Option<String> {
-        let municipality = city.replace(' ', "-").to_ascii_lowercase();
-        let province = province.to_ascii_uppercase();
+    #[cfg(feature = "std")]
+    pub fn encode_birth_place(
+        city: CFString<&str>,
+        province: CFString<&str>,
+    ) -> Option<&'static str> {
+        Place::by_name(&city, &province).first().map(|p| p.code)
+    }
 
-        let key = format!("{municipality},{province}");
+    /// Like [`CodiceFiscale::encode_birth_place`] but only considers the places
+    /// that existed on `date`, which matters for municipalities that were merged or renamed.
+    /// The province can also be one the municipality belonged to, see [`Place::resolve`]
+    #[cfg(feature = "std")]
+    pub fn encode_birth_place_on(
+        city: CFString<&str>,
+        province: CFString<&str>,
+        date: NaiveDate,
+    ) -> Option<&'static str> {
+        Place::valid_on(Place::by_name(&city, &province), date)
+            .or_else(|| Place::resolve(&city, &province, date).map(|resolved| resolved.place))
+            .map(|p| p.code)
+    }
 
-        if let Some(active_found) = ACTIVE_PLACES.get(&key) {
-            return Some(active_found.to_string());
+    /// Finds the code of the municipality that existed on `date` without knowing its province.
+    /// Fails with [`GenerationError::AmbiguousMunicipality`] when different municipalities
+    /// have this name, records of the same municipality in different provinces are fine
+    #[cfg(feature = "std")]
+    pub fn encode_municipality_on(
+        city: CFString<&str>,
+        date: NaiveDate,
+    ) -> Result<&'static str, GenerationError> {
+        let places = Place::by_municipality(&city);
+        let candidates = places.iter().filter(|p| p.is_valid_on(date));
+
+        let first = candidates
+            .clone()
+            .next()
+            .ok_or(GenerationError::BelfioreCodeNotFound)?;
+        if candidates.clone().all(|p| p.code == first.code) {
+            return Ok(first.code);
         }
 
-        INACTIVE_PLACES.get(&key).map(|p| p.to_string())
+        let mut provinces = candidates.map(|p| p.province).collect::<Vec<_>>();
+        provinces.sort_unstable();
+        provinces.dedup();
+        Err(GenerationError::AmbiguousMunicipality(provinces))
+    }
+
+    /// Finds the code of a foreign state valid on `date` from its name or ISO code
+    #[cfg(feature = "std")]
+    pub fn encode_foreign_birth_place(
+        country: CFString<&str>,
+        date: NaiveDate,
+    ) -> Option<&'static str> {
+        Country::find(&country, date).map(|c| c.code)
     }
 
     pub fn compute_checksum(partial_cf: &str) -> Result<char, GenerationError> {
         if partial_cf.len() != 15 {
             return Err(GenerationError::IncorrectChecksumInputLength);
         }
-
-        let partial_cf = partial_cf.to_uppercase();
-        let mut sum = 0;
-
-        // NOTE: This being 2 loops would eliminate the odd/even check
-        for (i, c) in partial_cf.bytes().enumerate() {
-            // NOTE: The odd/even tables are for 1 indexed numbers so we need to add 1
-            sum += match ((i + 1) % 2 == 0, c.is_ascii_digit()) {
-                (true, true) => CHECK_CODE_NUM_EVEN[(c - b'0') as usize],
-                (true, false) => CHECK_CODE_LET_EVEN[(c - b'A') as usize],
-                (false, true) => CHECK_CODE_NUM_ODD[(c - b'0') as usize],
-                (false, false) => CHECK_CODE_LET_ODD[(c - b'A') as usize],
-            };
+        if !partial_cf.bytes().all(|b| b.is_ascii_alphanumeric()) {
+            return Err(GenerationError::NonAlphanumericChecksumInput);
         }
 
-        sum %= 26;
-        Ok((sum as u8 + b'A') as char)
+        Ok(checksum(partial_cf.as_bytes()) as char)
     }
 
     pub fn is_omocode(&self) -> bool {
-        let cf = self.0.as_bytes();
         OMOCODE_POSITIONS
             .iter()
-            .any(|&pos| !cf[pos].is_ascii_digit())
+            .any(|&pos| !self.0[pos].is_ascii_digit())
+    }
+
+    /// Positions where a digit was substituted with a letter to make the code unique
+    #[cfg(feature = "std")]
+    pub fn omocode_positions(&self) -> Vec<usize> {
+        OMOCODE_POSITIONS
+            .into_iter()
+            .filter(|&pos| !self.0[pos].is_ascii_digit())
+            .collect()
+    }
+
+    /// Same as [`CodiceFiscale::suggest_corrections_on`] with today as the reference date
+    #[cfg(feature = "std")]
+    pub fn suggest_corrections(&self) -> Vec<CodiceFiscale> {
+        self.suggest_corrections_on(Utc::now().date_naive())
     }
 
-    pub fn all_omocodes(&self) -> Vec<CodiceFiscale> {
-        let cf = &self.0.as_bytes()[0..15];
+    /// Returns the codes that pass [`CodiceFiscale::parse_strict`] and are one substituted
+    /// character or one swap of adjacent characters away from this one. Codes with a known
+    /// place and a birth date that isn't after `reference` come first, then the ones that
+    /// only fix the check character and the ones that don't add omocode letters.
+    /// Valid codes have no suggestions
+    #[cfg(feature = "std")]
+    pub fn suggest_corrections_on(&self, reference: NaiveDate) -> Vec<CodiceFiscale> {
+        if Self::parse_strict(self.get()).is_ok() {
+            return Vec::new();
+        }
+
+        let mut candidates = Vec::new();
+        let mut bytes = self.0;
+
+        for position in 0..bytes.len() {
+            let original = bytes[position];
+            for replacement in (b'0'..=b'9').chain(b'A'..=b'Z') {
+                if replacement == original {
+                    continue;
+                }
+                bytes[position] = replacement;
+                candidates.extend(Self::parse_strict(std::str::from_utf8(&bytes).unwrap()));
+            }
+            bytes[position] = original;
+        }
+
+        for position in 0..bytes.len() - 1 {
+            if bytes[position] == bytes[position + 1] {
+                continue;
+            }
+            bytes.swap(position, position + 1);
+            candidates.extend(Self::parse_strict(std::str::from_utf8(&bytes).unwrap()));
+            bytes.swap(position, position + 1);
+        }
+
+        // NOTE: sort_by_cached_key is stable so equally plausible codes keep their order
+        candidates.sort_by_cached_key(|c| {
+            let fixes_check_character = c.0[..15] == self.0[..15];
+            let added_omocode = c.omocode_positions().len() > self.omocode_positions().len();
+            (
+                std::cmp::Reverse(c.plausibility(reference)),
+                !fixes_check_character,
+                added_omocode,
+            )
+        });
+        candidates
+    }
+
+    #[cfg(feature = "std")]
+    fn plausibility(&self, reference: NaiveDate) -> u8 {
+        let Ok(normalized) = self.normalize() else {
+            return 0;
+        };
+
+        let known_place = !Place::by_code(&normalized.get()[11..15]).is_empty();
+        let valid_date =
+            Self::decode_date_with(normalized.get(), reference, CenturyPolicy::PreferPast).is_ok();
+
+        known_place as u8 * 2 + valid_date as u8
+    }
+
+    /// Every omocode of the code, which is normalized first so omocodes of the same code give
+    /// the same result. The normalized code itself is not included
+    #[cfg(feature = "std")]
+    pub fn all_omocodes(&self) -> Result<Vec<CodiceFiscale>, ValidationError> {
         let subsets = OMOCODE_SUBSETS.get_or_init(|| all_subsets(&OMOCODE_POSITIONS));
+        let normalized = self.normalize()?;
 
         let mut all_cfs = Vec::new();
         for subset in subsets {
-            let mut code = cf.to_vec();
+            let mut code = normalized.0;
             for &position in subset {
                 let digit = code[position] - b'0';
                 code[position] = OMOCODE_LETTERS[digit as usize] as u8;
             }
-            let code_as_str = std::str::from_utf8(&code).unwrap();
-            let checksum = Self::compute_checksum(code_as_str).unwrap();
-            all_cfs.push(CodiceFiscale(format!("{code_as_str}{checksum}")));
+            code[15] = checksum(&code[0..15]);
+            all_cfs.push(CodiceFiscale(code));
         }
 
-        all_cfs
+        Ok(all_cfs)
     }
 
+    #[cfg(feature = "std")]
     pub fn decode_date(cf: &str) -> Result<(NaiveDate, Gender), ValidationError> {
-        let bytes = &cf.as_bytes()[6..11];
+        let (date, gender, _) =
+            Self::decode_date_with(cf, Utc::now().date_naive(), CenturyPolicy::PreferPast)?;
+        Ok((date, gender))
+    }
 
-        let mut year = ((bytes[0] - b'0') * 10 + (bytes[1] - b'0')) as i32;
+    /// Returns the most recent birth date that isn't after `reference` and, depending on
+    /// the policy, the date one century earlier as an alternative. `cf` must be a whole code,
+    /// omocodes are accepted
+    pub fn decode_date_with(
+        cf: &str,
+        reference: NaiveDate,
+        policy: CenturyPolicy,
+    ) -> Result<(NaiveDate, Gender, Option<NaiveDate>), ValidationError> {
+        // Normalizing leaves only digits in the year and day
+        let code = Self::from_str(cf)?.normalize()?;
+        let bytes = &code.0[6..11];
+
+        let year = ((bytes[0] - b'0') * 10 + (bytes[1] - b'0')) as i32;
         let month = bytes[2] as char;
         let mut day = (bytes[3] - b'0') * 10 + (bytes[4] - b'0');
 
@@ -189,117 +400,442 @@ impl CodiceFiscale {
             Gender::Male
         };
 
-        let curr_year = Utc::now().year();
-        year += if curr_year % 100 > year { 2000 } else { 1900 };
+        let month = (month + 1) as u32;
+        let day = day as u32;
+        let century = reference.year() - reference.year().rem_euclid(100);
 
-        let date = NaiveDate::from_ymd_opt(year, (month + 1) as u32, day as u32)
+        // Either the date is in the future or it's a 29th of February in a non leap year
+        let date = NaiveDate::from_ymd_opt(century + year, month, day)
+            .filter(|&d| d <= reference)
+            .or_else(|| NaiveDate::from_ymd_opt(century - 100 + year, month, day))
             .ok_or(ValidationError::InvalidDate)?;
 
-        Ok((date, gender))
+        let earlier = NaiveDate::from_ymd_opt(date.year() - 100, month, day);
+        let alternative = match policy {
+            CenturyPolicy::PreferPast => None,
+            CenturyPolicy::MaxAge(max_age) => {
+                earlier.filter(|&e| reference.years_since(e).is_some_and(|age| age <= max_age))
+            }
+            CenturyPolicy::Both => earlier,
+        };
+
+        Ok((date, gender, alternative))
     }
 
+    #[cfg(feature = "std")]
     pub fn decode_birth_place(cf: &str) -> Option<(String, String)> {
         let bytes = &cf.as_bytes()[11..15];
         let code = std::str::from_utf8(bytes).unwrap();
 
-        if let Some((&key, _)) = ACTIVE_PLACES.into_iter().find(|(_, &v)| v == code) {
-            return Some(Self::place_to_string(key));
+        Place::by_code(code)
+            .first()
+            .map(|p| (p.name.replace('-', " "), p.province.to_owned()))
+    }
+}
+
+/// The ascii letters of a name without separators, uppercase and split into the first four
+/// consonants and the first three vowels
+#[cfg(feature = "std")]
+struct NameLetters {
+    consonants: [u8; 4],
+    consonant_count: usize,
+    vowels: [u8; 3],
+    vowel_count: usize,
+}
+
+#[cfg(feature = "std")]
+impl NameLetters {
+    fn new(name: &str) -> Self {
+        let mut letters = Self {
+            consonants: [0; 4],
+            consonant_count: 0,
+            vowels: [0; 3],
+            vowel_count: 0,
         };
 
-        INACTIVE_PLACES
-            .into_iter()
-            .find(|(_, &v)| v == code)
-            .map(|(&key, _)| Self::place_to_string(key))
+        let bytes = name.bytes().filter(|&b| !SEPARATORS.contains(&(b as char)));
+        for b in bytes.map(|b| b.to_ascii_uppercase()) {
+            if VOWELS.contains(&b) {
+                if let Some(slot) = letters.vowels.get_mut(letters.vowel_count) {
+                    *slot = b;
+                    letters.vowel_count += 1;
+                }
+            } else if let Some(slot) = letters.consonants.get_mut(letters.consonant_count) {
+                *slot = b;
+                letters.consonant_count += 1;
+            }
+        }
+
+        letters
+    }
+
+    /// Fills the three letters with the consonants, then the vowels and then Xs
+    fn code(&self, consonant_count: usize) -> [u8; 3] {
+        let consonants = &self.consonants[..consonant_count];
+        let vowels = &self.vowels[..self.vowel_count];
+
+        let mut code = [b'X'; 3];
+        for (slot, &letter) in code.iter_mut().zip(consonants.iter().chain(vowels)) {
+            *slot = letter;
+        }
+        code
+    }
+}
+
+#[cfg(feature = "std")]
+fn last_name_code(last_name: &str) -> [u8; 3] {
+    let letters = NameLetters::new(last_name);
+    letters.code(letters.consonant_count.min(3))
+}
+
+/// First names with more than three consonants skip the second one
+#[cfg(feature = "std")]
+fn first_name_code(first_name: &str) -> [u8; 3] {
+    let letters = NameLetters::new(first_name);
+    let [first, _, third, fourth] = letters.consonants;
+
+    if letters.consonant_count > 3 {
+        [first, third, fourth]
+    } else {
+        letters.code(letters.consonant_count)
+    }
+}
+
+#[cfg(feature = "std")]
+fn birth_date_code(birth_date: NaiveDate, gender: Gender) -> [u8; 5] {
+    let year = birth_date.year().rem_euclid(100) as u8;
+    let month = MONTH_CODES[birth_date.month0() as usize] as u8;
+    let mut day = birth_date.day() as u8;
+
+    if gender == Gender::Female {
+        day += 40;
     }
 
-    fn place_to_string(place_key: &str) -> (String, String) {
-        let (city, province) = place_key.split_once(',').unwrap();
-        (city.replace('-', " "), province.to_owned())
+    [
+        b'0' + year / 10,
+        b'0' + year % 10,
+        month,
+        b'0' + day / 10,
+        b'0' + day % 10,
+    ]
+}
+
+/// The check character of the first 15 characters of a code, which must be ascii alphanumeric
+fn checksum(partial_cf: &[u8]) -> u8 {
+    let mut sum = 0;
+
+    // NOTE: This being 2 loops would eliminate the odd/even check
+    for (i, c) in partial_cf.iter().map(u8::to_ascii_uppercase).enumerate() {
+        // NOTE: The odd/even tables are for 1 indexed numbers so we need to add 1
+        sum += match ((i + 1) % 2 == 0, c.is_ascii_digit()) {
+            (true, true) => CHECK_CODE_NUM_EVEN[(c - b'0') as usize],
+            (true, false) => CHECK_CODE_LET_EVEN[(c - b'A') as usize],
+            (false, true) => CHECK_CODE_NUM_ODD[(c - b'0') as usize],
+            (false, false) => CHECK_CODE_LET_ODD[(c - b'A') as usize],
+        };
     }
+
+    (sum % 26) as u8 + b'A'
 }
 
-impl std::str::FromStr for CodiceFiscale {
+impl FromStr for CodiceFiscale {
     type Err = ValidationError;
 
+    /// Only checks the length and that every character is alphanumeric, see
+    /// [`CodiceFiscale::parse_strict`] for a complete validation. Letters are uppercased
     fn from_str(string: &str) -> Result<Self, Self::Err> {
         if string.len() != 16 {
             return Err(ValidationError::IncorrectLength(16));
         }
-        if string.chars().any(|c| !c.is_alphanumeric()) {
+        if string.chars().any(|c| !c.is_ascii_alphanumeric()) {
             return Err(ValidationError::NonAlphanumeric);
         }
 
-        Ok(Self(string.to_owned()))
+        let mut bytes = [0; 16];
+        bytes.copy_from_slice(string.as_bytes());
+        bytes.make_ascii_uppercase();
+
+        Ok(Self(bytes))
     }
 }
 
+impl core::fmt::Display for CodiceFiscale {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        f.write_str(self.get())
+    }
+}
+
+impl core::fmt::Debug for CodiceFiscale {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        f.debug_tuple("CodiceFiscale").field(&self.get()).finish()
+    }
+}
+
+#[cfg(feature = "std")]
 impl TryFrom<&Subject> for CodiceFiscale {
     type Error = GenerationError;
 
+    /// Builds the code in place without allocating. The birth place is checked again since the
+    /// fields of a subject can be set without [`Subject::new`]
     fn try_from(value: &Subject) -> Result<Self, Self::Error> {
-        let mut output = String::with_capacity(16);
+        value.birth_place.validate()?;
+
+        let mut output = [0; 16];
+
+        output[0..3].copy_from_slice(&last_name_code(&value.last_name));
+        output[3..6].copy_from_slice(&first_name_code(&value.first_name));
+        output[6..11].copy_from_slice(&birth_date_code(value.birth_date, value.gender));
+
+        let place_code = match &value.birth_place {
+            BirthPlace::Italy {
+                municipality,
+                province: Some(province),
+            } => Self::encode_birth_place_on(
+                municipality.as_deref(),
+                province.as_deref(),
+                value.birth_date,
+            )
+            .ok_or(Self::Error::BelfioreCodeNotFound)?,
+            BirthPlace::Italy {
+                municipality,
+                province: None,
+            } => Self::encode_municipality_on(municipality.as_deref(), value.birth_date)?,
+            BirthPlace::Foreign { country } => {
+                Self::encode_foreign_birth_place(country.as_deref(), value.birth_date)
+                    .ok_or(Self::Error::BelfioreCodeNotFound)?
+            }
+            BirthPlace::Belfiore { code } => code.get(),
+        };
+        output[11..15].copy_from_slice(place_code.as_bytes());
+        output[15] = checksum(&output[0..15]);
 
-        output.push_str(&Self::encode_last_name(value.last_name.as_deref()));
-        output.push_str(&Self::encode_first_name(value.first_name.as_deref()));
-        output.push_str(&Self::encode_birth_date(value.birth_date, value.gender));
+        Ok(Self(output))
+    }
+}
 
-        let place_code = Self::encode_birth_place(
-            value.birth_place.as_deref(),
-            value.birth_province.as_deref(),
-        )
-        .ok_or(Self::Error::BelfioreCodeNotFound)?;
-        output.push_str(&place_code);
-        output.push(Self::compute_checksum(&output)?);
+#[cfg(feature = "serde")]
+impl serde::Serialize for CodiceFiscale {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(self.get())
+    }
+}
 
-        Ok(Self(output))
+/// Deserializing goes through [`CodiceFiscale::parse_strict`]
+#[cfg(feature = "serde")]
+impl<'de> serde::Deserialize<'de> for CodiceFiscale {
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let string = String::deserialize(deserializer)?;
+        Self::parse_strict(&string).map_err(serde::de::Error::custom)
     }
 }
 
+/// Some variants only exist with the `std` feature, so matches need a wildcard arm
 #[derive(Debug, PartialEq, Eq)]
+#[non_exhaustive]
 pub enum GenerationError {
     BelfioreCodeNotFound,
+    /// The province is not in the [`crate::Province`] registry
+    #[cfg(feature = "std")]
+    UnknownProvince,
+    /// The municipality was given without a province and different municipalities have its
+    /// name, these are their provinces
+    #[cfg(feature = "std")]
+    AmbiguousMunicipality(Vec<&'static str>),
+    /// The input of [`CodiceFiscale::compute_checksum`] is not 15 characters long
     IncorrectChecksumInputLength,
+    /// The input of [`CodiceFiscale::compute_checksum`] has characters that aren't ASCII
+    /// letters or digits
+    NonAlphanumericChecksumInput,
+    NonNumericChecksumInput,
+    NumberOutOfRange,
+    /// The input of [`crate::PartitaIva::compute_check_digit`] is not 10 digits long
+    IncorrectPartitaIvaChecksumInputLength,
+}
+
+/// The kind of character expected at a position of a codice fiscale
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub enum CharacterClass {
+    Letter,
+    DigitOrOmocodeLetter,
 }
 
 #[derive(Debug, PartialEq, Eq)]
 pub enum ValidationError {
     IncorrectLength(usize),
+    /// The position is 0 based
+    InvalidCharacter {
+        position: usize,
+        expected: CharacterClass,
+    },
+    InvalidChecksum,
     InvalidDate,
+    InvalidDay,
     InvalidMonthLetter,
     InvalidOmocodeLetter,
     NonAlphanumeric,
+    NonNumeric,
+    NotANaturalPerson,
     UnknownPlaceCode,
 }
 
 impl Error for GenerationError {}
 impl Error for ValidationError {}
-impl std::fmt::Display for GenerationError {
-    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+impl core::fmt::Display for GenerationError {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
         let message = match self {
             Self::BelfioreCodeNotFound => "could not find belfiore code for this city and province",
+            #[cfg(feature = "std")]
+            Self::UnknownProvince => "the province is not a current or historic italian province",
+            #[cfg(feature = "std")]
+            Self::AmbiguousMunicipality(provinces) => {
+                let provinces = provinces.join(", ");
+                return write!(
+                    f,
+                    "more than one municipality has this name, in the provinces {provinces}: \
+                     specify the province"
+                );
+            }
             Self::IncorrectChecksumInputLength => "checksum input must be 15 characters long",
+            Self::NonAlphanumericChecksumInput => {
+                "checksum input must only have ASCII letters and digits"
+            }
+            Self::NonNumericChecksumInput => "partita IVA checksum input must only have digits",
+            Self::NumberOutOfRange => "serial number or office code is out of range",
+            Self::IncorrectPartitaIvaChecksumInputLength => {
+                "partita IVA checksum input must be 10 characters long"
+            }
         };
         write!(f, "{message}")
     }
 }
-impl std::fmt::Display for ValidationError {
-    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+impl core::fmt::Display for ValidationError {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
         let message = match self {
             Self::InvalidDate => "the codice fiscale does not contain a valid date",
             Self::IncorrectLength(len) => return write!(f, "input must be {len} characters long"),
+            Self::InvalidCharacter { position, expected } => {
+                let expected = match expected {
+                    CharacterClass::Letter => "a letter",
+                    CharacterClass::DigitOrOmocodeLetter => "a digit or an omocode letter",
+                };
+                return write!(f, "character at position {position} must be {expected}");
+            }
+            Self::InvalidChecksum => "the check character does not match the rest of the code",
+            Self::InvalidDay => "the day must be between 1 and 31, or 41 and 71 for women",
             Self::InvalidOmocodeLetter => "codice fiscale contains invalid omocode letter",
             Self::InvalidMonthLetter => "the letter used for the month is invalid",
             Self::NonAlphanumeric => "characters must be alphabetical letters or numbers",
+            Self::NonNumeric => "characters must be numbers",
+            Self::NotANaturalPerson => "the numeric code belongs to an entity, not a person",
             Self::UnknownPlaceCode => "could not find place associated with code in codice fiscale",
         };
         write!(f, "{message}")
     }
 }
 
+/// Tests of the parts that are available without `std`, they also run with it
 #[cfg(test)]
-mod tests {
-    use std::str::FromStr;
+mod core_tests {
+    use super::*;
 
+    #[test]
+    fn test_parse_strict() {
+        let code = CodiceFiscale::parse_strict("glngcr56p10g224q").unwrap();
+        assert_eq!(code.get(), "GLNGCR56P10G224Q");
+
+        assert_eq!(
+            CodiceFiscale::parse_strict("GLNGCR56P10G224"),
+            Err(ValidationError::IncorrectLength(16))
+        );
+        assert_eq!(
+            CodiceFiscale::parse_strict("GLNGCR56P10G2A4Q"),
+            Err(ValidationError::InvalidCharacter {
+                position: 13,
+                expected: CharacterClass::DigitOrOmocodeLetter
+            })
+        );
+        assert_eq!(
+            CodiceFiscale::parse_strict("GLNGCR56P10G224A"),
+            Err(ValidationError::InvalidChecksum)
+        );
+    }
+
+    #[test]
+    fn test_normalize() {
+        let code = CodiceFiscale::parse_strict("CCCFBAU5D03L21VB").unwrap();
+        assert!(code.is_omocode());
+
+        let normalized = code.normalize().unwrap();
+        assert_eq!(normalized.get(), "CCCFBA85D03L219P");
+        assert!(!normalized.is_omocode());
+    }
+
+    #[test]
+    fn test_compute_checksum() {
+        assert_eq!(CodiceFiscale::compute_checksum("GLNGCR56P10G224"), Ok('Q'));
+        assert_eq!(
+            CodiceFiscale::compute_checksum("GLNGCR56P10"),
+            Err(GenerationError::IncorrectChecksumInputLength)
+        );
+        assert_eq!(
+            CodiceFiscale::compute_checksum("GLNGCR56P10G22-"),
+            Err(GenerationError::NonAlphanumericChecksumInput)
+        );
+        assert_eq!(
+            CodiceFiscale::compute_checksum("GLNGCR56P10G22è"),
+            Err(GenerationError::IncorrectChecksumInputLength)
+        );
+    }
+
+    #[test]
+    fn test_decode_date_with() {
+        let reference = NaiveDate::from_ymd_opt(2024, 6, 1).unwrap();
+
+        let (date, gender, alternative) =
+            CodiceFiscale::decode_date_with("RSSMRA70A41F205Z", reference, CenturyPolicy::Both)
+                .unwrap();
+        assert_eq!(date, NaiveDate::from_ymd_opt(1970, 1, 1).unwrap());
+        assert_eq!(gender, Gender::Female);
+        assert_eq!(alternative, NaiveDate::from_ymd_opt(1870, 1, 1));
+
+        assert_eq!(
+            CodiceFiscale::decode_date_with("RSSMRA70B30F205Z", reference, CenturyPolicy::Both),
+            Err(ValidationError::InvalidDate)
+        );
+    }
+
+    #[test]
+    fn test_decode_date_with_malformed() {
+        let reference = NaiveDate::from_ymd_opt(2024, 6, 1).unwrap();
+        let policy = CenturyPolicy::PreferPast;
+
+        assert_eq!(
+            CodiceFiscale::decode_date_with("RSSMRA70A", reference, policy),
+            Err(ValidationError::IncorrectLength(16))
+        );
+        assert_eq!(
+            CodiceFiscale::decode_date_with("RSSMRA7-A41F205Z", reference, policy),
+            Err(ValidationError::NonAlphanumeric)
+        );
+        assert_eq!(
+            CodiceFiscale::decode_date_with("RSSMRA7AA41F205Z", reference, policy),
+            Err(ValidationError::InvalidOmocodeLetter)
+        );
+        assert_eq!(
+            CodiceFiscale::decode_date_with("RSSMRA70A99F205Z", reference, policy),
+            Err(ValidationError::InvalidDate)
+        );
+
+        // The omocode of RSSMRA70A41F205Z
+        let (date, gender, _) =
+            CodiceFiscale::decode_date_with("RSSMRATLA41F205Z", reference, policy).unwrap();
+        assert_eq!(date, NaiveDate::from_ymd_opt(1970, 1, 1).unwrap());
+        assert_eq!(gender, Gender::Female);
+    }
+}
+
+#[cfg(all(test, feature = "std"))]
+mod tests {
     use super::*;
 
     #[test]
@@ -326,6 +862,24 @@ mod tests {
         assert_eq!(&CodiceFiscale::encode_last_name(name), "YUX");
     }
 
+    #[test]
+    fn test_last_name_code_apostrophe() {
+        let name = CFString::new("D'Angelo").unwrap();
+        assert_eq!(&CodiceFiscale::encode_last_name(name), "DNG");
+
+        let name = CFString::new("Dell'Acqua").unwrap();
+        assert_eq!(&CodiceFiscale::encode_last_name(name), "DLL");
+
+        let name = CFString::new("O'Neil").unwrap();
+        assert_eq!(&CodiceFiscale::encode_last_name(name), "NLO");
+    }
+
+    #[test]
+    fn test_last_name_code_hyphen() {
+        let name = CFString::new("Rossi-Bianchi").unwrap();
+        assert_eq!(&CodiceFiscale::encode_last_name(name), "RSS");
+    }
+
     #[test]
     fn test_first_name_consonants() {
         let name = CFString::new("Massimo").unwrap();
@@ -344,6 +898,15 @@ mod tests {
         assert_eq!(&CodiceFiscale::encode_first_name(name), "MTR");
     }
 
+    #[test]
+    fn test_first_name_punctuation() {
+        let name = CFString::new("Gian-Luca").unwrap();
+        assert_eq!(&CodiceFiscale::encode_first_name(name), "GLC");
+
+        let name = CFString::new("Maria  Teresa").unwrap();
+        assert_eq!(&CodiceFiscale::encode_first_name(name), "MTR");
+    }
+
     #[test]
     fn test_first_name_short() {
         let name = CFString::new("Li").unwrap();
@@ -392,7 +955,7 @@ mod tests {
         let place = CFString::new("Abano").unwrap();
         let province = CFString::new("PD").unwrap();
         let res = CodiceFiscale::encode_birth_place(place, province);
-        assert_eq!(res.as_deref(), Some("A001"));
+        assert_eq!(res, Some("A001"));
     }
 
     #[test]
@@ -400,7 +963,81 @@ mod tests {
         let place = CFString::new("I dont exist").unwrap();
         let province = CFString::new("PD").unwrap();
         let res = CodiceFiscale::encode_birth_place(place, province);
-        assert_eq!(res.as_deref(), None);
+        assert_eq!(res, None);
+    }
+
+    #[test]
+    fn test_birth_place_on_date() {
+        let place = CFString::new("Abano").unwrap();
+        let province = CFString::new("PD").unwrap();
+        let date = NaiveDate::from_ymd_opt(1900, 1, 1).unwrap();
+        let res = CodiceFiscale::encode_birth_place_on(place, province, date);
+        assert_eq!(res, Some("A001"));
+
+        for province in ["MI", "LO"] {
+            let place = CFString::new("Abbadia Cerreto").unwrap();
+            let province = CFString::new(province).unwrap();
+            let res = CodiceFiscale::encode_birth_place_on(place, province, date);
+            assert_eq!(res, Some("A004"));
+        }
+
+        let encode = |year| {
+            let place = CFString::new("Longarone").unwrap();
+            let province = CFString::new("BL").unwrap();
+            let date = NaiveDate::from_ymd_opt(year, 1, 1).unwrap();
+            CodiceFiscale::encode_birth_place_on(place, province, date)
+        };
+        assert_eq!(encode(1956), Some("E672"));
+        assert_eq!(encode(2020), Some("M342"));
+    }
+
+    #[test]
+    fn test_merged_municipalities_on_date() {
+        let encode = |name, year| {
+            let date = NaiveDate::from_ymd_opt(year, 6, 1).unwrap();
+            CodiceFiscale::encode_municipality_on(CFString::new(name).unwrap(), date)
+        };
+
+        // Valsamoggia replaced Bazzano and four other municipalities in 2014
+        assert_eq!(encode("Bazzano", 1980), Ok("A726"));
+        assert_eq!(
+            encode("Bazzano", 2020),
+            Err(GenerationError::BelfioreCodeNotFound)
+        );
+        assert_eq!(
+            encode("Valsamoggia", 1980),
+            Err(GenerationError::BelfioreCodeNotFound)
+        );
+        assert_eq!(encode("Valsamoggia", 2020), Ok("M320"));
+
+        // Corigliano-Rossano replaced Corigliano Calabro and Rossano in 2018
+        assert_eq!(encode("Rossano", 2000), Ok("H579"));
+        assert_eq!(encode("Corigliano-Rossano", 2020), Ok("M403"));
+        assert_eq!(
+            encode("Corigliano-Rossano", 2000),
+            Err(GenerationError::BelfioreCodeNotFound)
+        );
+    }
+
+    #[test]
+    fn test_municipality_without_province() {
+        let date = NaiveDate::from_ymd_opt(1956, 9, 10).unwrap();
+        let encode =
+            |name| CodiceFiscale::encode_municipality_on(CFString::new(name).unwrap(), date);
+
+        assert_eq!(encode("Padova"), Ok("G224"));
+        // Also recorded in the province of Milano before Monza e Brianza was created
+        assert_eq!(encode("Monza"), Ok("F704"));
+        assert_eq!(
+            encode("Peglio"),
+            Err(GenerationError::AmbiguousMunicipality(vec![
+                "CO", "PS", "PU"
+            ]))
+        );
+        assert_eq!(
+            encode("I dont exist"),
+            Err(GenerationError::BelfioreCodeNotFound)
+        );
     }
 
     #[test]
@@ -444,8 +1081,10 @@ mod tests {
             last_name: "Rossi".try_into().unwrap(),
             birth_date: NaiveDate::from_ymd_opt(1970, 1, 1).unwrap(),
             gender: Gender::Female,
-            birth_place: "Milano".try_into().unwrap(),
-            birth_province: "Mi".try_into().unwrap(),
+            birth_place: BirthPlace::Italy {
+                municipality: "Milano".try_into().unwrap(),
+                province: Some("Mi".try_into().unwrap()),
+            },
         };
 
         assert_eq!(
@@ -461,8 +1100,10 @@ mod tests {
             last_name: "Galan".try_into().unwrap(),
             birth_date: NaiveDate::from_ymd_opt(1956, 9, 10).unwrap(),
             gender: Gender::Male,
-            birth_place: "Padova".try_into().unwrap(),
-            birth_province: "PD".try_into().unwrap(),
+            birth_place: BirthPlace::Italy {
+                municipality: "Padova".try_into().unwrap(),
+                province: Some("PD".try_into().unwrap()),
+            },
         };
 
         assert_eq!(
@@ -471,6 +1112,121 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_encodes_from_belfiore_code() {
+        let mut subject = Subject {
+            first_name: "Giancarlo".try_into().unwrap(),
+            last_name: "Galan".try_into().unwrap(),
+            birth_date: NaiveDate::from_ymd_opt(1956, 9, 10).unwrap(),
+            gender: Gender::Male,
+            birth_place: BirthPlace::Belfiore {
+                code: "G224".try_into().unwrap(),
+            },
+        };
+        assert_eq!(
+            CodiceFiscale::try_from(&subject).unwrap().get(),
+            "GLNGCR56P10G224Q"
+        );
+
+        // Longarone, which was merged into a new municipality with the same name in 2014
+        subject.birth_place = BirthPlace::Belfiore {
+            code: "E672".try_into().unwrap(),
+        };
+        let code = CodiceFiscale::try_from(&subject).unwrap();
+        assert_eq!(&code.get()[11..15], "E672");
+        assert!(code.verify(&subject).unwrap().is_match());
+    }
+
+    #[test]
+    fn test_encodes_with_historic_province() {
+        let mut subject = Subject {
+            first_name: "Giancarlo".try_into().unwrap(),
+            last_name: "Galan".try_into().unwrap(),
+            birth_date: NaiveDate::from_ymd_opt(1956, 9, 10).unwrap(),
+            gender: Gender::Male,
+            birth_place: BirthPlace::Italy {
+                municipality: "Forli".try_into().unwrap(),
+                province: Some("FO".try_into().unwrap()),
+            },
+        };
+        let code = CodiceFiscale::try_from(&subject).unwrap();
+        assert_eq!(&code.get()[11..15], "D704");
+        assert_eq!(subject.province_change(), None);
+
+        let birth_place = code.decode().unwrap().birth_place;
+        let province = birth_place.province().unwrap();
+        assert_eq!(
+            (province.name, province.region.name()),
+            ("Forlì-Cesena", "Emilia-Romagna")
+        );
+
+        subject.birth_place = BirthPlace::Italy {
+            municipality: "Forli".try_into().unwrap(),
+            province: Some("XX".try_into().unwrap()),
+        };
+        assert_eq!(
+            CodiceFiscale::try_from(&subject),
+            Err(GenerationError::UnknownProvince)
+        );
+    }
+
+    #[test]
+    fn test_province_change() {
+        let mut subject = Subject {
+            first_name: "Giancarlo".try_into().unwrap(),
+            last_name: "Galan".try_into().unwrap(),
+            birth_date: NaiveDate::from_ymd_opt(2000, 1, 1).unwrap(),
+            gender: Gender::Male,
+            birth_place: BirthPlace::Italy {
+                municipality: "Abbadia Cerreto".try_into().unwrap(),
+                province: Some("MI".try_into().unwrap()),
+            },
+        };
+        assert_eq!(
+            &CodiceFiscale::try_from(&subject).unwrap().get()[11..15],
+            "A004"
+        );
+        assert_eq!(
+            subject.province_change(),
+            Some(crate::ProvinceChange {
+                given: "MI",
+                recorded: "LO"
+            })
+        );
+
+        // Before 1992 it was in the province of Milano
+        subject.birth_date = NaiveDate::from_ymd_opt(1980, 1, 1).unwrap();
+        assert_eq!(subject.province_change(), None);
+    }
+
+    #[test]
+    fn test_encodes_without_province() {
+        let mut subject = Subject {
+            first_name: "Giancarlo".try_into().unwrap(),
+            last_name: "Galan".try_into().unwrap(),
+            birth_date: NaiveDate::from_ymd_opt(1956, 9, 10).unwrap(),
+            gender: Gender::Male,
+            birth_place: BirthPlace::Italy {
+                municipality: "Padova".try_into().unwrap(),
+                province: None,
+            },
+        };
+        assert_eq!(
+            CodiceFiscale::try_from(&subject).unwrap().get(),
+            "GLNGCR56P10G224Q"
+        );
+
+        subject.birth_place = BirthPlace::Italy {
+            municipality: "Castro".try_into().unwrap(),
+            province: None,
+        };
+        let error = CodiceFiscale::try_from(&subject).unwrap_err();
+        assert_eq!(
+            error.to_string(),
+            "more than one municipality has this name, in the provinces BG, LE: specify the province"
+        );
+    }
+
     #[test]
     fn test_all_omocodes() {
         // From https://github.com/fabiocaccamo/python-codicefiscale/blob/main/tests/test_codicefiscale.py#L598
@@ -605,30 +1361,174 @@ mod tests {
         ];
         expected.sort();
 
-        let all_omocodes = CodiceFiscale("CCCFBA85D03L219P".into()).all_omocodes();
-        let mut all_strs = all_omocodes
-            .iter()
-            .map(|cf| cf.0.as_str())
-            .collect::<Vec<_>>();
+        let all_omocodes = CodiceFiscale(*b"CCCFBA85D03L219P").all_omocodes().unwrap();
+        let mut all_strs = all_omocodes.iter().map(|cf| cf.get()).collect::<Vec<_>>();
         all_strs.sort();
 
         assert_eq!(all_strs, expected);
+
+        let omocode = CodiceFiscale(*b"CCCFBAU5D03L21VB");
+        assert_eq!(omocode.all_omocodes().unwrap(), all_omocodes);
+        assert_eq!(
+            CodiceFiscale(*b"CCCFBAA5D03L219P").all_omocodes(),
+            Err(ValidationError::InvalidOmocodeLetter)
+        );
     }
 
     #[test]
     fn test_normalize_from_omocode() {
         let expected = "CCCFBA85D03L219P";
 
-        let cf = CodiceFiscale("CCCFBA85DLPLNM9W".into());
+        let cf = CodiceFiscale(*b"CCCFBA85DLPLNM9W");
         assert_eq!(cf.normalize().unwrap().get(), expected);
 
-        let cf = CodiceFiscale("CCCFBAURDLPLNMVU".into());
+        let cf = CodiceFiscale(*b"CCCFBAURDLPLNMVU");
         assert_eq!(cf.normalize().unwrap().get(), expected);
 
-        let cf = CodiceFiscale("CCCFBA85D03LN19E".into());
+        let cf = CodiceFiscale(*b"CCCFBA85D03LN19E");
         assert_eq!(cf.normalize().unwrap().get(), expected);
     }
 
+    #[test]
+    fn test_parse_strict() {
+        let code = CodiceFiscale::parse_strict("rssmra70a41f205z").unwrap();
+        assert_eq!(code.get(), "RSSMRA70A41F205Z");
+
+        assert!(CodiceFiscale::parse_strict("CCCFBAURDLPLNMVU").is_ok());
+    }
+
+    #[test]
+    fn test_parse_strict_character_class() {
+        assert_eq!(
+            CodiceFiscale::parse_strict("RSS1RA70A41F205Z").unwrap_err(),
+            ValidationError::InvalidCharacter {
+                position: 3,
+                expected: CharacterClass::Letter
+            }
+        );
+        assert_eq!(
+            CodiceFiscale::parse_strict("RSSMRA7AA41F205Z").unwrap_err(),
+            ValidationError::InvalidCharacter {
+                position: 7,
+                expected: CharacterClass::DigitOrOmocodeLetter
+            }
+        );
+        assert_eq!(
+            CodiceFiscale::parse_strict("RSSMRA70A41F2O5Z").unwrap_err(),
+            ValidationError::InvalidCharacter {
+                position: 13,
+                expected: CharacterClass::DigitOrOmocodeLetter
+            }
+        );
+    }
+
+    #[test]
+    fn test_parse_strict_date() {
+        assert_eq!(
+            CodiceFiscale::parse_strict("RSSMRA70F41F205Z").unwrap_err(),
+            ValidationError::InvalidMonthLetter
+        );
+        assert_eq!(
+            CodiceFiscale::parse_strict("RSSMRA70A35F205Z").unwrap_err(),
+            ValidationError::InvalidDay
+        );
+        assert_eq!(
+            CodiceFiscale::parse_strict("RSSMRA70A00F205Z").unwrap_err(),
+            ValidationError::InvalidDay
+        );
+    }
+
+    #[test]
+    fn test_parse_strict_checksum() {
+        assert_eq!(
+            CodiceFiscale::parse_strict("RSSMRA70A41F205A").unwrap_err(),
+            ValidationError::InvalidChecksum
+        );
+    }
+
+    #[test]
+    fn test_from_str_uppercases() {
+        let lower = CodiceFiscale::from_str("rssmra70a41f205z").unwrap();
+        let upper = CodiceFiscale::from_str("RSSMRA70A41F205Z").unwrap();
+
+        assert_eq!(lower, upper);
+        assert_eq!(lower.get(), "RSSMRA70A41F205Z");
+        assert_eq!(lower.to_string(), "RSSMRA70A41F205Z");
+
+        let codes = std::collections::HashSet::from([lower, upper]);
+        assert_eq!(codes.len(), 1);
+    }
+
+    #[test]
+    fn test_from_str_unicode_digits() {
+        assert_eq!(
+            CodiceFiscale::from_str("RSSMRA70A41F2²Z").unwrap_err(),
+            ValidationError::NonAlphanumeric
+        );
+    }
+
+    fn reference() -> NaiveDate {
+        NaiveDate::from_ymd_opt(2024, 6, 1).unwrap()
+    }
+
+    #[test]
+    fn test_suggest_corrections_substitution() {
+        let code = CodiceFiscale::from_str("GLNGCR56P10G225Q").unwrap();
+        let suggestions = code.suggest_corrections_on(reference());
+
+        assert!(suggestions.iter().any(|c| c.get() == "GLNGCR56P10G224Q"));
+        assert!(suggestions
+            .iter()
+            .all(|c| CodiceFiscale::parse_strict(c.get()).is_ok()));
+        assert_eq!(suggestions[0].plausibility(reference()), 3);
+    }
+
+    #[test]
+    fn test_suggest_corrections_transposition() {
+        let code = CodiceFiscale::from_str("GLNGCR65P10G224Q").unwrap();
+        let suggestions = code.suggest_corrections_on(reference());
+
+        assert!(suggestions.iter().any(|c| c.get() == "GLNGCR56P10G224Q"));
+    }
+
+    #[test]
+    fn test_suggest_corrections_ranking() {
+        let code = CodiceFiscale::from_str("GLNGCR56P10G225Q").unwrap();
+        let scores = code
+            .suggest_corrections_on(reference())
+            .iter()
+            .map(|c| c.plausibility(reference()))
+            .collect::<Vec<_>>();
+
+        assert!(scores.windows(2).all(|w| w[0] >= w[1]));
+    }
+
+    #[test]
+    fn test_suggest_corrections_check_character_first() {
+        let code = CodiceFiscale::from_str("RSSMRA70A41F205Y").unwrap();
+        let suggestions = code.suggest_corrections_on(reference());
+        assert_eq!(suggestions[0].get(), "RSSMRA70A41F205Z");
+
+        // Among equally plausible codes the ones without omocode letters come first
+        let keys = suggestions[1..]
+            .iter()
+            .map(|c| {
+                (
+                    std::cmp::Reverse(c.plausibility(reference())),
+                    c.is_omocode(),
+                )
+            })
+            .collect::<Vec<_>>();
+        assert!(keys.windows(2).all(|w| w[0] <= w[1]));
+        assert!(keys.iter().any(|(_, omocode)| *omocode));
+    }
+
+    #[test]
+    fn test_suggest_corrections_valid_code() {
+        let code = CodiceFiscale::from_str("GLNGCR56P10G224Q").unwrap();
+        assert!(code.suggest_corrections_on(reference()).is_empty());
+    }
+
     #[test]
     fn test_decode_date() {
         let expected_date = NaiveDate::from_ymd_opt(1985, 4, 3).unwrap();
@@ -639,6 +1539,47 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_decode_date_reference() {
+        let reference = NaiveDate::from_ymd_opt(2024, 6, 1).unwrap();
+        let policy = CenturyPolicy::PreferPast;
+
+        let (date, _, alternative) =
+            CodiceFiscale::decode_date_with("CCCFBA24D03L219P", reference, policy).unwrap();
+        assert_eq!(date, NaiveDate::from_ymd_opt(2024, 4, 3).unwrap());
+        assert_eq!(alternative, None);
+
+        let (date, _, _) =
+            CodiceFiscale::decode_date_with("CCCFBA24H03L219P", reference, policy).unwrap();
+        assert_eq!(date, NaiveDate::from_ymd_opt(1924, 6, 3).unwrap());
+    }
+
+    #[test]
+    fn test_decode_date_max_age() {
+        let reference = NaiveDate::from_ymd_opt(2024, 6, 1).unwrap();
+        let policy = CenturyPolicy::MaxAge(120);
+
+        let (date, _, alternative) =
+            CodiceFiscale::decode_date_with("CCCFBA10D03L219P", reference, policy).unwrap();
+        assert_eq!(date, NaiveDate::from_ymd_opt(2010, 4, 3).unwrap());
+        assert_eq!(alternative, NaiveDate::from_ymd_opt(1910, 4, 3));
+
+        let (_, _, alternative) =
+            CodiceFiscale::decode_date_with("CCCFBA85D03L219P", reference, policy).unwrap();
+        assert_eq!(alternative, None);
+    }
+
+    #[test]
+    fn test_decode_date_both() {
+        let reference = NaiveDate::from_ymd_opt(2024, 6, 1).unwrap();
+
+        let (date, _, alternative) =
+            CodiceFiscale::decode_date_with("CCCFBA85D03L219P", reference, CenturyPolicy::Both)
+                .unwrap();
+        assert_eq!(date, NaiveDate::from_ymd_opt(1985, 4, 3).unwrap());
+        assert_eq!(alternative, NaiveDate::from_ymd_opt(1885, 4, 3));
+    }
+
     #[test]
     fn test_decode_active_place() {
         let expected_city = "pedivigliano".to_owned();
@@ -657,6 +1598,31 @@ mod tests {
         assert_eq!(res, Some((expected_city, expected_province)));
     }
 
+    #[test]
+    fn test_decode_place_of_birth_date() {
+        let reference = NaiveDate::from_ymd_opt(2024, 1, 1).unwrap();
+        let province = |code: &str| {
+            let code = CodiceFiscale::parse_strict(code).unwrap();
+            let decoded = code
+                .decode_with(reference, CenturyPolicy::PreferPast)
+                .unwrap();
+            match decoded.birth_place {
+                DecodedBirthPlace::Italy { province, .. } => (province, decoded.place_status),
+                DecodedBirthPlace::Foreign(_) => panic!("born in Italy"),
+            }
+        };
+
+        // Abbadia Cerreto moved from the province of Milano to Lodi in 1992
+        assert_eq!(
+            province("GLNGCR80A01A004A"),
+            ("MI".to_owned(), PlaceStatus::Active)
+        );
+        assert_eq!(
+            province("GLNGCR00A01A004I"),
+            ("LO".to_owned(), PlaceStatus::Active)
+        );
+    }
+
     #[test]
     fn test_decode_unknown_place() {
         let res = CodiceFiscale::decode_birth_place("CCCFBA85D03C008P");
@@ -672,17 +1638,104 @@ mod tests {
         assert_eq!(res, Some((expected_city, expected_province)));
     }
 
+    #[test]
+    fn test_birth_places() {
+        let code = CodiceFiscale::from_str("CCCFBA85D03A001P").unwrap();
+        let places = code.birth_places().unwrap();
+        assert_eq!(places.len(), 2);
+        assert_eq!(places[0].name, "abano-terme");
+        assert_eq!(places[1].name, "abano");
+    }
+
+    #[test]
+    fn test_birth_places_unknown() {
+        let code = CodiceFiscale::from_str("CCCFBA85D03C008P").unwrap();
+        assert_eq!(code.birth_places(), Err(ValidationError::UnknownPlaceCode));
+    }
+
     #[test]
     fn test_decode_data() {
         let code = CodiceFiscale::from_str("GLNGCR56P10G224Q").unwrap();
 
         let expected = DecodedData {
+            last_name: "GLN".into(),
+            first_name: "GCR".into(),
             birth_date: NaiveDate::from_ymd_opt(1956, 9, 10).unwrap(),
+            alternative_birth_date: None,
             gender: Gender::Male,
-            birth_place: "padova".into(),
-            birth_province: "PD".into(),
+            birth_place: DecodedBirthPlace::Italy {
+                municipality: "Padova".into(),
+                province: "PD".into(),
+            },
+            belfiore_code: "G224".into(),
+            place_status: PlaceStatus::Active,
+            omocode_positions: vec![],
         };
 
         assert_eq!(code.decode().unwrap(), expected);
     }
+
+    #[test]
+    fn test_decode_omocode_data() {
+        let code = CodiceFiscale::from_str("CCCFBA85D03LN19E").unwrap();
+        let decoded = code.decode().unwrap();
+
+        assert!(decoded.is_omocode());
+        assert_eq!(decoded.omocode_positions, vec![12]);
+        assert_eq!(decoded.belfiore_code, "L219");
+    }
+
+    #[test]
+    fn test_decode_place_status() {
+        let code = CodiceFiscale::from_str("CCCFBA85D03A005R").unwrap();
+        let decoded = code.decode().unwrap();
+        assert_eq!(decoded.place_status, PlaceStatus::Active);
+        assert_eq!(
+            decoded.birth_place,
+            DecodedBirthPlace::Italy {
+                municipality: "Abbadia Lariana".into(),
+                province: "LC".into(),
+            }
+        );
+
+        let code = CodiceFiscale::from_str("CCCFBA85D03B723P").unwrap();
+        assert_eq!(code.decode().unwrap().place_status, PlaceStatus::Inactive);
+
+        let code = CodiceFiscale::from_str("CCCFBA85D03Z404P").unwrap();
+        assert_eq!(code.decode().unwrap().place_status, PlaceStatus::Foreign);
+    }
+
+    #[test]
+    fn test_encode_foreign_by_iso() {
+        let mut subject = Subject {
+            first_name: "Maria".try_into().unwrap(),
+            last_name: "Rossi".try_into().unwrap(),
+            birth_date: NaiveDate::from_ymd_opt(1970, 1, 1).unwrap(),
+            gender: Gender::Female,
+            birth_place: BirthPlace::Foreign {
+                country: "DE".try_into().unwrap(),
+            },
+        };
+        let by_iso = CodiceFiscale::encode(&subject).unwrap();
+        assert_eq!(&by_iso.get()[11..15], "Z112");
+
+        subject.birth_place = BirthPlace::Foreign {
+            country: "Germania".try_into().unwrap(),
+        };
+        let by_name = CodiceFiscale::encode(&subject).unwrap();
+        assert_eq!(by_iso.get(), by_name.get());
+    }
+
+    #[test]
+    fn test_decode_foreign() {
+        let code = CodiceFiscale::from_str("RSSMRA70A41Z404U").unwrap();
+
+        let DecodedBirthPlace::Foreign(country) = code.decode().unwrap().birth_place else {
+            panic!("expected a foreign birth place");
+        };
+        assert_eq!(country.code, "Z404");
+        assert_eq!(country.iso_alpha2, Some("US"));
+        assert_eq!(country.iso_alpha3, Some("USA"));
+        assert_eq!(country.continent, Some(crate::Continent::NorthAmerica));
+    }
 }
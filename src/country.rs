@@ -0,0 +1,157 @@
+use chrono::NaiveDate;
+
+use crate::place::{ascii_uppercase, display_name, Place};
+
+include!(concat!(env!("OUT_DIR"), "/countries.rs"));
+
+/// The continent groups of the Belfiore codes for foreign states
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "snake_case"))]
+pub enum Continent {
+    Europe,
+    Asia,
+    Africa,
+    NorthAmerica,
+    CentralAmerica,
+    SouthAmerica,
+    Oceania,
+}
+
+impl Continent {
+    /// Foreign states are grouped by the first digit of their code: `Z1xx` for Europe,
+    /// `Z2xx` for Asia and so on. Codes outside of these ranges return `None`
+    pub const fn from_code(code: &str) -> Option<Self> {
+        let bytes = code.as_bytes();
+        if bytes.len() != 4 || bytes[0] != b'Z' {
+            return None;
+        }
+
+        match bytes[1] {
+            b'1' => Some(Self::Europe),
+            b'2' => Some(Self::Asia),
+            b'3' => Some(Self::Africa),
+            b'4' => Some(Self::NorthAmerica),
+            b'5' => Some(Self::CentralAmerica),
+            b'6' => Some(Self::SouthAmerica),
+            b'7' => Some(Self::Oceania),
+            _ => None,
+        }
+    }
+}
+
+/// A foreign state, current or historic, with a `Z` Belfiore code
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct Country {
+    pub code: &'static str,
+    pub name: &'static str,
+    /// ISO 3166-1 codes, `None` for states that don't exist anymore
+    pub iso_alpha2: Option<&'static str>,
+    pub iso_alpha3: Option<&'static str>,
+    pub continent: Option<Continent>,
+}
+
+impl Country {
+    pub fn by_code(code: &str) -> Option<Self> {
+        let mut buffer = [0; 4];
+        ascii_uppercase(code, &mut buffer)
+            .and_then(|code| COUNTRIES_BY_CODE.get(code))
+            .copied()
+    }
+
+    /// Accepts both ISO 3166-1 alpha-2 and alpha-3 codes
+    pub fn by_iso(iso: &str) -> Option<Self> {
+        let mut buffer = [0; 3];
+        ascii_uppercase(iso, &mut buffer)
+            .and_then(|iso| COUNTRIES_BY_ISO.get(iso))
+            .and_then(|code| Self::by_code(code))
+    }
+
+    /// Finds a country by ISO code or by one of its Italian or English names,
+    /// only considering the states that existed on `date`
+    pub fn find(name_or_iso: &str, date: NaiveDate) -> Option<Self> {
+        if let Some(country) = Self::by_iso(name_or_iso) {
+            return Some(country);
+        }
+
+        Place::valid_on(Place::by_name(name_or_iso, "EE"), date)
+            .and_then(|place| Self::by_code(place.code))
+    }
+
+    pub fn display_name(&self) -> String {
+        display_name(self.name)
+    }
+
+    /// Every name this country has been recorded with
+    pub fn names(&self) -> &'static [Place] {
+        Place::by_code(self.code)
+    }
+}
+
+/// Countries are deserialized from their code, the rest of the fields come from the tables
+#[cfg(feature = "serde")]
+impl<'de> serde::Deserialize<'de> for Country {
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        #[derive(serde::Deserialize)]
+        struct CountryCode {
+            code: String,
+        }
+
+        let CountryCode { code } = CountryCode::deserialize(deserializer)?;
+        Country::by_code(&code)
+            .ok_or_else(|| serde::de::Error::custom(format!("unknown country code {code}")))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn today() -> NaiveDate {
+        NaiveDate::from_ymd_opt(2024, 1, 1).unwrap()
+    }
+
+    #[test]
+    fn test_continent_from_code() {
+        assert_eq!(Continent::from_code("Z112"), Some(Continent::Europe));
+        assert_eq!(Continent::from_code("Z210"), Some(Continent::Asia));
+        assert_eq!(
+            Continent::from_code("Z514"),
+            Some(Continent::CentralAmerica)
+        );
+        assert_eq!(Continent::from_code("Z700"), Some(Continent::Oceania));
+        assert_eq!(Continent::from_code("A001"), None);
+    }
+
+    #[test]
+    fn test_by_iso() {
+        let alpha2 = Country::by_iso("de").unwrap();
+        let alpha3 = Country::by_iso("DEU").unwrap();
+
+        assert_eq!(alpha2, alpha3);
+        assert_eq!(alpha2.code, "Z112");
+        assert_eq!(alpha2.continent, Some(Continent::Europe));
+    }
+
+    #[test]
+    fn test_find_by_name() {
+        assert_eq!(Country::find("Germania", today()).unwrap().code, "Z112");
+        assert_eq!(Country::find("Germany", today()).unwrap().code, "Z112");
+        assert_eq!(
+            Country::find("Stati Uniti d America", today())
+                .unwrap()
+                .code,
+            "Z404"
+        );
+        assert_eq!(Country::find("Atlantide", today()), None);
+    }
+
+    #[test]
+    fn test_historic_country() {
+        let country = Country::by_code("Z135").unwrap();
+        assert_eq!(country.iso_alpha2, None);
+        assert_eq!(country.continent, Some(Continent::Europe));
+        assert!(country.names().len() > 1);
+    }
+}
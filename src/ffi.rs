@@ -0,0 +1,610 @@
+//! C API declared in `include/codice_fiscale.h`, which is checked against the header
+//! generated by the build script when testing with the `ffi` feature. Link the library built with
+//! `cargo rustc --lib --release --features ffi --crate-type staticlib` (or `cdylib`).
+//!
+//! Every function returns a [`CfStatus`] and writes its result through an output pointer.
+//! Codes are written to caller owned buffers of [`CF_CODE_SIZE`] bytes, the only memory owned
+//! by the library is the birth place of [`CfDecoded`], released with [`cf_decoded_free`]
+
+use std::ffi::{c_char, c_int, CStr, CString};
+use std::ptr;
+use std::sync::OnceLock;
+
+use chrono::{Datelike, NaiveDate, Utc};
+
+use crate::{
+    BirthPlace, CFString, CFStringError, CenturyPolicy, CodiceFiscale, DecodedBirthPlace, Gender,
+    GenerationError, PlaceStatus, Subject, ValidationError,
+};
+
+/// Size of the buffers that receive a codice fiscale, 16 characters and the terminating NUL
+pub const CF_CODE_SIZE: usize = 17;
+
+/// The messages returned by [`cf_status_message`], indexed by status
+static STATUS_MESSAGES: OnceLock<Vec<CString>> = OnceLock::new();
+
+/// The outcome of a call. The values are stable, new ones are only ever added at the end
+#[repr(C)]
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub enum CfStatus {
+    Ok = 0,
+    /// A required pointer argument was NULL
+    NullPointer = 1,
+    /// A string argument was not valid UTF-8
+    InvalidUtf8 = 2,
+    IncorrectLength = 3,
+    InvalidCharacter = 4,
+    InvalidChecksum = 5,
+    InvalidDate = 6,
+    InvalidDay = 7,
+    InvalidMonthLetter = 8,
+    InvalidOmocodeLetter = 9,
+    NonAlphanumeric = 10,
+    NonNumeric = 11,
+    NotANaturalPerson = 12,
+    UnknownPlaceCode = 13,
+    BelfioreCodeNotFound = 14,
+    IncorrectChecksumInputLength = 15,
+    NonNumericChecksumInput = 16,
+    NumberOutOfRange = 17,
+    /// A name, municipality, province or country with characters that aren't allowed
+    InvalidName = 18,
+    /// The gender was neither 'M' nor 'F'
+    InvalidGender = 19,
+    /// The birth date or the reference date doesn't exist
+    InvalidInputDate = 20,
+    /// The province is NULL and different municipalities have the same name
+    AmbiguousMunicipality = 21,
+    /// The province is not a current or historic Italian province
+    UnknownProvince = 22,
+}
+
+impl CfStatus {
+    /// Every status, indexed by its value
+    const ALL: [Self; 23] = [
+        Self::Ok,
+        Self::NullPointer,
+        Self::InvalidUtf8,
+        Self::IncorrectLength,
+        Self::InvalidCharacter,
+        Self::InvalidChecksum,
+        Self::InvalidDate,
+        Self::InvalidDay,
+        Self::InvalidMonthLetter,
+        Self::InvalidOmocodeLetter,
+        Self::NonAlphanumeric,
+        Self::NonNumeric,
+        Self::NotANaturalPerson,
+        Self::UnknownPlaceCode,
+        Self::BelfioreCodeNotFound,
+        Self::IncorrectChecksumInputLength,
+        Self::NonNumericChecksumInput,
+        Self::NumberOutOfRange,
+        Self::InvalidName,
+        Self::InvalidGender,
+        Self::InvalidInputDate,
+        Self::AmbiguousMunicipality,
+        Self::UnknownProvince,
+    ];
+
+    /// The message of the Rust error that the status comes from, errors that carry data like
+    /// a position get a message that doesn't depend on it
+    fn message(self) -> String {
+        let message = match self {
+            Self::Ok => "ok",
+            Self::NullPointer => "a required pointer was NULL",
+            Self::InvalidUtf8 => "a string was not valid UTF-8",
+            Self::IncorrectLength => "the input has the wrong length",
+            Self::InvalidCharacter => "a character is not allowed at its position",
+            Self::InvalidChecksum => return ValidationError::InvalidChecksum.to_string(),
+            Self::InvalidDate => return ValidationError::InvalidDate.to_string(),
+            Self::InvalidDay => return ValidationError::InvalidDay.to_string(),
+            Self::InvalidMonthLetter => return ValidationError::InvalidMonthLetter.to_string(),
+            Self::InvalidOmocodeLetter => return ValidationError::InvalidOmocodeLetter.to_string(),
+            Self::NonAlphanumeric => return ValidationError::NonAlphanumeric.to_string(),
+            Self::NonNumeric => return ValidationError::NonNumeric.to_string(),
+            Self::NotANaturalPerson => return ValidationError::NotANaturalPerson.to_string(),
+            Self::UnknownPlaceCode => return ValidationError::UnknownPlaceCode.to_string(),
+            Self::BelfioreCodeNotFound => return GenerationError::BelfioreCodeNotFound.to_string(),
+            Self::IncorrectChecksumInputLength => {
+                return GenerationError::IncorrectChecksumInputLength.to_string()
+            }
+            Self::NonNumericChecksumInput => {
+                return GenerationError::NonNumericChecksumInput.to_string()
+            }
+            Self::NumberOutOfRange => return GenerationError::NumberOutOfRange.to_string(),
+            Self::InvalidName => {
+                "names must only have letters, spaces, apostrophes, hyphens or dots"
+            }
+            Self::InvalidGender => "the gender must be 'M' or 'F'",
+            Self::InvalidInputDate => "the date does not exist",
+            Self::AmbiguousMunicipality => {
+                "more than one municipality has this name, specify the province"
+            }
+            Self::UnknownProvince => return GenerationError::UnknownProvince.to_string(),
+        };
+        message.to_owned()
+    }
+}
+
+impl From<ValidationError> for CfStatus {
+    fn from(error: ValidationError) -> Self {
+        match error {
+            ValidationError::IncorrectLength(_) => Self::IncorrectLength,
+            ValidationError::InvalidCharacter { .. } => Self::InvalidCharacter,
+            ValidationError::InvalidChecksum => Self::InvalidChecksum,
+            ValidationError::InvalidDate => Self::InvalidDate,
+            ValidationError::InvalidDay => Self::InvalidDay,
+            ValidationError::InvalidMonthLetter => Self::InvalidMonthLetter,
+            ValidationError::InvalidOmocodeLetter => Self::InvalidOmocodeLetter,
+            ValidationError::NonAlphanumeric => Self::NonAlphanumeric,
+            ValidationError::NonNumeric => Self::NonNumeric,
+            ValidationError::NotANaturalPerson => Self::NotANaturalPerson,
+            ValidationError::UnknownPlaceCode => Self::UnknownPlaceCode,
+        }
+    }
+}
+
+impl From<GenerationError> for CfStatus {
+    fn from(error: GenerationError) -> Self {
+        match error {
+            GenerationError::BelfioreCodeNotFound => Self::BelfioreCodeNotFound,
+            // NOTE: The C API has no partita IVA functions
+            GenerationError::IncorrectChecksumInputLength
+            | GenerationError::IncorrectPartitaIvaChecksumInputLength => {
+                Self::IncorrectChecksumInputLength
+            }
+            GenerationError::NonNumericChecksumInput => Self::NonNumericChecksumInput,
+            GenerationError::NonAlphanumericChecksumInput => Self::NonAlphanumeric,
+            GenerationError::NumberOutOfRange => Self::NumberOutOfRange,
+            GenerationError::AmbiguousMunicipality(_) => Self::AmbiguousMunicipality,
+            GenerationError::UnknownProvince => Self::UnknownProvince,
+        }
+    }
+}
+
+impl From<CFStringError> for CfStatus {
+    fn from(_: CFStringError) -> Self {
+        Self::InvalidName
+    }
+}
+
+#[repr(C)]
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub enum CfPlaceStatus {
+    Active,
+    Inactive,
+    Foreign,
+}
+
+#[repr(C)]
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub struct CfDate {
+    pub year: i32,
+    /// From 1 to 12
+    pub month: u32,
+    pub day: u32,
+}
+
+/// The data to encode. People born in Italy have a municipality, a province that can be NULL
+/// when the name is unambiguous, and a NULL country. People born abroad have a country, either
+/// its name or its ISO code
+#[repr(C)]
+#[derive(Debug, Clone, Copy)]
+pub struct CfSubject {
+    pub first_name: *const c_char,
+    pub last_name: *const c_char,
+    pub birth_date: CfDate,
+    /// 'M' or 'F'
+    pub gender: c_char,
+    pub municipality: *const c_char,
+    pub province: *const c_char,
+    pub country: *const c_char,
+}
+
+/// The data contained in a codice fiscale, the strings are NUL terminated
+#[repr(C)]
+#[derive(Debug)]
+pub struct CfDecoded {
+    pub last_name: [c_char; 4],
+    pub first_name: [c_char; 4],
+    pub birth_date: CfDate,
+    /// 'M' or 'F'
+    pub gender: c_char,
+    pub belfiore_code: [c_char; 5],
+    /// "EE" for people born abroad
+    pub province: [c_char; 3],
+    /// Name of the municipality or the country, owned by the library
+    pub birth_place: *mut c_char,
+    pub place_status: CfPlaceStatus,
+    pub is_omocode: bool,
+}
+
+/// Returns a static NUL terminated description of a [`CfStatus`], or a generic message for
+/// values that are not a status
+#[no_mangle]
+pub extern "C" fn cf_status_message(status: c_int) -> *const c_char {
+    let messages = STATUS_MESSAGES.get_or_init(|| {
+        CfStatus::ALL
+            .iter()
+            .map(|status| CString::new(status.message()).unwrap())
+            .collect()
+    });
+
+    usize::try_from(status)
+        .ok()
+        .and_then(|status| messages.get(status))
+        .map_or(c"unknown status".as_ptr(), |message| message.as_ptr())
+}
+
+/// Checks every character, the date and the check character of a codice fiscale
+///
+/// # Safety
+///
+/// `code` must be NULL or point to a NUL terminated string
+#[no_mangle]
+pub unsafe extern "C" fn cf_validate(code: *const c_char) -> CfStatus {
+    status(str_arg(code).and_then(|code| Ok(CodiceFiscale::parse_strict(code).map(drop)?)))
+}
+
+/// Validates a codice fiscale like [`cf_validate`] and writes it in uppercase to `out`
+///
+/// # Safety
+///
+/// `code` must be NULL or point to a NUL terminated string, `out` must be NULL or point to
+/// [`CF_CODE_SIZE`] writable bytes
+#[no_mangle]
+pub unsafe extern "C" fn cf_parse(code: *const c_char, out: *mut c_char) -> CfStatus {
+    status((|| {
+        let code = CodiceFiscale::parse_strict(str_arg(code)?)?;
+        write_code(&code, out)
+    })())
+}
+
+/// Writes the check character of the first 15 characters of a code to `out`
+///
+/// # Safety
+///
+/// `partial` must be NULL or point to a NUL terminated string, `out` must be NULL or point to
+/// a writable byte
+#[no_mangle]
+pub unsafe extern "C" fn cf_compute_checksum(partial: *const c_char, out: *mut c_char) -> CfStatus {
+    status((|| {
+        let partial = str_arg(partial)?;
+        if out.is_null() {
+            return Err(CfStatus::NullPointer);
+        }
+        *out = CodiceFiscale::compute_checksum(partial)? as c_char;
+        Ok(())
+    })())
+}
+
+/// Decodes a codice fiscale. The century of the birth date is the most recent one that's not
+/// after `reference`, today if it's NULL. On success `out` must be released with
+/// [`cf_decoded_free`], on failure it's left untouched
+///
+/// # Safety
+///
+/// `code` must be NULL or point to a NUL terminated string, `reference` must be NULL or point
+/// to a date and `out` must be NULL or point to a writable [`CfDecoded`]
+#[no_mangle]
+pub unsafe extern "C" fn cf_decode(
+    code: *const c_char,
+    reference: *const CfDate,
+    out: *mut CfDecoded,
+) -> CfStatus {
+    status((|| {
+        let code = CodiceFiscale::parse_strict(str_arg(code)?)?;
+        let reference = match reference.as_ref() {
+            Some(date) => to_date(date)?,
+            None => Utc::now().date_naive(),
+        };
+        let out = out.as_mut().ok_or(CfStatus::NullPointer)?;
+
+        let decoded = code.decode_with(reference, CenturyPolicy::PreferPast)?;
+        let (birth_place, province) = match &decoded.birth_place {
+            DecodedBirthPlace::Italy {
+                municipality,
+                province,
+            } => (municipality.clone(), province.as_str()),
+            DecodedBirthPlace::Foreign(country) => (country.display_name(), "EE"),
+        };
+
+        *out = CfDecoded {
+            last_name: to_c_array(&decoded.last_name),
+            first_name: to_c_array(&decoded.first_name),
+            birth_date: CfDate {
+                year: decoded.birth_date.year(),
+                month: decoded.birth_date.month(),
+                day: decoded.birth_date.day(),
+            },
+            gender: gender_char(decoded.gender),
+            belfiore_code: to_c_array(&decoded.belfiore_code),
+            province: to_c_array(province),
+            // NOTE: Display names only contain ascii letters and spaces
+            birth_place: CString::new(birth_place).unwrap().into_raw(),
+            place_status: match decoded.place_status {
+                PlaceStatus::Active => CfPlaceStatus::Active,
+                PlaceStatus::Inactive => CfPlaceStatus::Inactive,
+                PlaceStatus::Foreign => CfPlaceStatus::Foreign,
+            },
+            is_omocode: decoded.is_omocode(),
+        };
+        Ok(())
+    })())
+}
+
+/// Releases the memory owned by a [`CfDecoded`], calling it again on the same struct is a no-op
+///
+/// # Safety
+///
+/// `decoded` must be NULL or point to a [`CfDecoded`] filled by [`cf_decode`]
+#[no_mangle]
+pub unsafe extern "C" fn cf_decoded_free(decoded: *mut CfDecoded) {
+    let Some(decoded) = decoded.as_mut() else {
+        return;
+    };
+
+    if !decoded.birth_place.is_null() {
+        drop(CString::from_raw(decoded.birth_place));
+        decoded.birth_place = ptr::null_mut();
+    }
+}
+
+/// Encodes the codice fiscale of a subject and writes it to `out`
+///
+/// # Safety
+///
+/// `subject` must be NULL or point to a [`CfSubject`] whose strings are NULL or NUL
+/// terminated, `out` must be NULL or point to [`CF_CODE_SIZE`] writable bytes
+#[no_mangle]
+pub unsafe extern "C" fn cf_encode(subject: *const CfSubject, out: *mut c_char) -> CfStatus {
+    status((|| {
+        let subject = subject.as_ref().ok_or(CfStatus::NullPointer)?;
+
+        let birth_place = if subject.country.is_null() {
+            BirthPlace::Italy {
+                municipality: name_arg(subject.municipality)?,
+                province: if subject.province.is_null() {
+                    None
+                } else {
+                    Some(name_arg(subject.province)?)
+                },
+            }
+        } else {
+            BirthPlace::Foreign {
+                country: name_arg(subject.country)?,
+            }
+        };
+
+        let subject = Subject::new(
+            name_arg(subject.first_name)?,
+            name_arg(subject.last_name)?,
+            to_date(&subject.birth_date)?,
+            match subject.gender as u8 {
+                b'M' | b'm' => Gender::Male,
+                b'F' | b'f' => Gender::Female,
+                _ => return Err(CfStatus::InvalidGender),
+            },
+            birth_place,
+        )?;
+
+        write_code(&CodiceFiscale::encode(&subject)?, out)
+    })())
+}
+
+fn status(result: Result<(), CfStatus>) -> CfStatus {
+    result.err().unwrap_or(CfStatus::Ok)
+}
+
+unsafe fn str_arg<'a>(string: *const c_char) -> Result<&'a str, CfStatus> {
+    if string.is_null() {
+        return Err(CfStatus::NullPointer);
+    }
+
+    CStr::from_ptr(string)
+        .to_str()
+        .map_err(|_| CfStatus::InvalidUtf8)
+}
+
+unsafe fn name_arg(string: *const c_char) -> Result<CFString<String>, CfStatus> {
+    Ok(CFString::new(str_arg(string)?.to_owned())?)
+}
+
+fn to_date(date: &CfDate) -> Result<NaiveDate, CfStatus> {
+    NaiveDate::from_ymd_opt(date.year, date.month, date.day).ok_or(CfStatus::InvalidInputDate)
+}
+
+fn gender_char(gender: Gender) -> c_char {
+    match gender {
+        Gender::Male => b'M' as c_char,
+        Gender::Female => b'F' as c_char,
+    }
+}
+
+/// Copies an ascii string to a NUL terminated array, `N` must be longer than the string
+fn to_c_array<const N: usize>(string: &str) -> [c_char; N] {
+    let mut array = [0; N];
+    for (slot, b) in array.iter_mut().zip(string.bytes()) {
+        *slot = b as c_char;
+    }
+    array
+}
+
+unsafe fn write_code(code: &CodiceFiscale, out: *mut c_char) -> Result<(), CfStatus> {
+    if out.is_null() {
+        return Err(CfStatus::NullPointer);
+    }
+
+    let array = to_c_array::<CF_CODE_SIZE>(code.get());
+    ptr::copy_nonoverlapping(array.as_ptr(), out, CF_CODE_SIZE);
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn code_str(buffer: &[c_char; CF_CODE_SIZE]) -> &str {
+        unsafe { CStr::from_ptr(buffer.as_ptr()) }.to_str().unwrap()
+    }
+
+    #[test]
+    fn test_validate() {
+        unsafe {
+            assert_eq!(cf_validate(c"GLNGCR56P10G224Q".as_ptr()), CfStatus::Ok);
+            assert_eq!(
+                cf_validate(c"GLNGCR56P10G224A".as_ptr()),
+                CfStatus::InvalidChecksum
+            );
+            assert_eq!(cf_validate(c"GLNG".as_ptr()), CfStatus::IncorrectLength);
+            assert_eq!(cf_validate(ptr::null()), CfStatus::NullPointer);
+        }
+    }
+
+    #[test]
+    fn test_parse() {
+        let mut buffer = [0; CF_CODE_SIZE];
+        unsafe {
+            assert_eq!(
+                cf_parse(c"glngcr56p10g224q".as_ptr(), buffer.as_mut_ptr()),
+                CfStatus::Ok
+            );
+        }
+        assert_eq!(code_str(&buffer), "GLNGCR56P10G224Q");
+    }
+
+    #[test]
+    fn test_compute_checksum() {
+        let mut checksum = 0;
+        unsafe {
+            assert_eq!(
+                cf_compute_checksum(c"GLNGCR56P10G224".as_ptr(), &mut checksum),
+                CfStatus::Ok
+            );
+            assert_eq!(checksum as u8, b'Q');
+            assert_eq!(
+                cf_compute_checksum(c"GLNGCR56P10G22-".as_ptr(), &mut checksum),
+                CfStatus::NonAlphanumeric
+            );
+        }
+    }
+
+    #[test]
+    fn test_decode_and_free() {
+        let reference = CfDate {
+            year: 2024,
+            month: 1,
+            day: 1,
+        };
+        let mut decoded = std::mem::MaybeUninit::<CfDecoded>::uninit();
+
+        unsafe {
+            let status = cf_decode(
+                c"GLNGCR56P10G224Q".as_ptr(),
+                &reference,
+                decoded.as_mut_ptr(),
+            );
+            assert_eq!(status, CfStatus::Ok);
+
+            let mut decoded = decoded.assume_init();
+            assert_eq!(
+                decoded.birth_date,
+                CfDate {
+                    year: 1956,
+                    month: 9,
+                    day: 10
+                }
+            );
+            assert_eq!(decoded.gender as u8, b'M');
+            assert_eq!(CStr::from_ptr(decoded.birth_place).to_str(), Ok("Padova"));
+            assert_eq!(CStr::from_ptr(decoded.province.as_ptr()).to_str(), Ok("PD"));
+            assert_eq!(decoded.place_status, CfPlaceStatus::Active);
+
+            cf_decoded_free(&mut decoded);
+            assert!(decoded.birth_place.is_null());
+            cf_decoded_free(&mut decoded);
+        }
+    }
+
+    #[test]
+    fn test_encode() {
+        let mut subject = CfSubject {
+            first_name: c"Giancarlo".as_ptr(),
+            last_name: c"Galan".as_ptr(),
+            birth_date: CfDate {
+                year: 1956,
+                month: 9,
+                day: 10,
+            },
+            gender: b'M' as c_char,
+            municipality: c"Padova".as_ptr(),
+            province: c"PD".as_ptr(),
+            country: ptr::null(),
+        };
+        let mut buffer = [0; CF_CODE_SIZE];
+
+        unsafe {
+            assert_eq!(cf_encode(&subject, buffer.as_mut_ptr()), CfStatus::Ok);
+            assert_eq!(code_str(&buffer), "GLNGCR56P10G224Q");
+
+            subject.province = ptr::null();
+            assert_eq!(cf_encode(&subject, buffer.as_mut_ptr()), CfStatus::Ok);
+            assert_eq!(code_str(&buffer), "GLNGCR56P10G224Q");
+
+            subject.municipality = c"Peglio".as_ptr();
+            assert_eq!(
+                cf_encode(&subject, buffer.as_mut_ptr()),
+                CfStatus::AmbiguousMunicipality
+            );
+
+            subject.country = c"US".as_ptr();
+            assert_eq!(cf_encode(&subject, buffer.as_mut_ptr()), CfStatus::Ok);
+            assert_eq!(&code_str(&buffer)[11..15], "Z404");
+
+            subject.gender = b'X' as c_char;
+            assert_eq!(
+                cf_encode(&subject, buffer.as_mut_ptr()),
+                CfStatus::InvalidGender
+            );
+        }
+    }
+
+    #[test]
+    fn test_status_message() {
+        let message = |status| {
+            let message = unsafe { CStr::from_ptr(cf_status_message(status)) };
+            message.to_str().unwrap()
+        };
+
+        assert_eq!(
+            message(CfStatus::IncorrectChecksumInputLength as c_int),
+            GenerationError::IncorrectChecksumInputLength.to_string()
+        );
+        assert_eq!(message(CfStatus::Ok as c_int), "ok");
+        assert_eq!(message(-1), "unknown status");
+        assert_eq!(message(CfStatus::ALL.len() as c_int), "unknown status");
+    }
+
+    #[test]
+    fn test_status_values() {
+        for (value, status) in CfStatus::ALL.into_iter().enumerate() {
+            assert_eq!(status as usize, value);
+        }
+    }
+
+    /// Run with `UPDATE_HEADER=1` to update the checked-in header after changing the API
+    #[test]
+    fn test_header_is_up_to_date() {
+        let generated = include_str!(concat!(env!("OUT_DIR"), "/codice_fiscale.h"));
+        let path = concat!(env!("CARGO_MANIFEST_DIR"), "/include/codice_fiscale.h");
+
+        if std::env::var_os("UPDATE_HEADER").is_some() {
+            std::fs::write(path, generated).unwrap();
+        }
+        assert!(
+            std::fs::read_to_string(path).unwrap() == generated,
+            "{path} is out of date, run the tests with UPDATE_HEADER=1 to update it"
+        );
+    }
+}
@@ -1,36 +1,247 @@
+//! Without the default `std` feature only the parts that need neither `std` nor an allocator
+//! are available: parsing and validating a [`CodiceFiscale`], computing its check character,
+//! normalizing omocodes and decoding the birth date with [`CodiceFiscale::decode_date_with`]
+#![cfg_attr(not(feature = "std"), no_std)]
+
+#[cfg(feature = "batch")]
+pub mod batch;
+#[cfg(feature = "std")]
+mod belfiore;
 mod codice_fiscale;
+#[cfg(feature = "std")]
+mod country;
+#[cfg(feature = "ffi")]
+pub mod ffi;
+#[cfg(feature = "std")]
+mod partita_iva;
+#[cfg(feature = "std")]
+mod place;
+#[cfg(feature = "std")]
+mod province;
+#[cfg(feature = "std")]
+mod search;
+#[cfg(feature = "std")]
 mod string;
+#[cfg(feature = "std")]
+mod transliterate;
+#[cfg(feature = "std")]
+mod verification;
+#[cfg(feature = "wasm")]
+pub mod wasm;
 
+#[cfg(feature = "std")]
+pub use belfiore::{BelfioreCode, BelfioreCodeError};
 pub use chrono::NaiveDate;
-pub use codice_fiscale::{CodiceFiscale, ACTIVE_PLACES, INACTIVE_PLACES};
-pub use string::CFString;
+pub use codice_fiscale::{CharacterClass, CodiceFiscale, GenerationError, ValidationError};
+#[cfg(feature = "std")]
+pub use country::{Continent, Country};
+#[cfg(feature = "std")]
+pub use partita_iva::{AnyCodiceFiscale, NumericKind, PartitaIva, ProvisionalCodiceFiscale};
+#[cfg(feature = "std")]
+pub use place::{Place, ProvinceChange, ResolvedPlace, ACTIVE_PLACES, INACTIVE_PLACES};
+#[cfg(feature = "std")]
+pub use province::{Province, Region};
+#[cfg(feature = "std")]
+pub use search::{MatchKind, PlaceMatch, SearchOptions};
+#[cfg(feature = "std")]
+pub use string::{CFString, CFStringError};
+#[cfg(feature = "std")]
+pub use transliterate::Transliteration;
+#[cfg(feature = "std")]
+pub use verification::{FieldCheck, Verification};
 
 #[derive(Debug, PartialEq, Eq, Clone, Copy)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "snake_case"))]
 pub enum Gender {
     Male,
     Female,
 }
 
+#[cfg(feature = "std")]
 #[derive(Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Subject {
     pub first_name: CFString<String>,
     pub last_name: CFString<String>,
     pub birth_date: NaiveDate,
     pub gender: Gender,
-    pub birth_place: CFString<String>,
-    pub birth_province: CFString<String>,
+    pub birth_place: BirthPlace,
 }
 
+/// Deserializing checks the province like [`BirthPlace::validate`]
+#[cfg(feature = "std")]
 #[derive(Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(
+    feature = "serde",
+    serde(remote = "Self", tag = "kind", rename_all = "snake_case")
+)]
+pub enum BirthPlace {
+    /// The province can be left out when no other municipality has ever had the same name
+    Italy {
+        municipality: CFString<String>,
+        province: Option<CFString<String>>,
+    },
+    /// The country can be either its Italian or English name or its ISO 3166-1 alpha-2 or
+    /// alpha-3 code
+    Foreign { country: CFString<String> },
+    /// The code is used as is, it's not checked against the birth date
+    Belfiore { code: BelfioreCode },
+}
+
+#[cfg(feature = "std")]
+impl Subject {
+    /// Builds a subject whose birth place passes [`BirthPlace::validate`]
+    pub fn new(
+        first_name: CFString<String>,
+        last_name: CFString<String>,
+        birth_date: NaiveDate,
+        gender: Gender,
+        birth_place: BirthPlace,
+    ) -> Result<Self, GenerationError> {
+        birth_place.validate()?;
+        Ok(Self {
+            first_name,
+            last_name,
+            birth_date,
+            gender,
+            birth_place,
+        })
+    }
+
+    /// Set when the birth municipality didn't belong to the given province on the birth date
+    /// but was recorded in it at another time, like Abbadia Cerreto given in Milano after
+    /// the province of Lodi was created. The code is encoded from the recorded province
+    pub fn province_change(&self) -> Option<ProvinceChange> {
+        let BirthPlace::Italy {
+            municipality,
+            province: Some(province),
+        } = &self.birth_place
+        else {
+            return None;
+        };
+
+        if Place::valid_on(Place::by_name(municipality, province), self.birth_date).is_some() {
+            return None;
+        }
+        Place::resolve(municipality, province, self.birth_date)?.province_change
+    }
+}
+
+#[cfg(feature = "std")]
+impl BirthPlace {
+    /// Checks that the province of a municipality is in the [`Province`] registry, "EE" is
+    /// accepted for the foreign states recorded as municipalities
+    pub fn validate(&self) -> Result<(), GenerationError> {
+        match self {
+            Self::Italy {
+                province: Some(province),
+                ..
+            } if **province != codice_fiscale::FOREIGN_PROVINCE
+                && Province::by_code(province).is_none() =>
+            {
+                Err(GenerationError::UnknownProvince)
+            }
+            _ => Ok(()),
+        }
+    }
+}
+
+// NOTE: `remote = "Self"` turns the derives into inherent functions, these wrap them
+#[cfg(feature = "serde")]
+impl serde::Serialize for BirthPlace {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        Self::serialize(self, serializer)
+    }
+}
+
+#[cfg(feature = "serde")]
+impl<'de> serde::Deserialize<'de> for BirthPlace {
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let birth_place = Self::deserialize(deserializer)?;
+        birth_place.validate().map_err(serde::de::Error::custom)?;
+        Ok(birth_place)
+    }
+}
+
+/// How to pick the century of the two digit birth year found in a codice fiscale
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "snake_case"))]
+pub enum CenturyPolicy {
+    /// The most recent date that isn't after the reference date
+    PreferPast,
+    /// Like `PreferPast` but also reports the date one century earlier when the subject
+    /// would be at most this many years old
+    MaxAge(u32),
+    /// Always reports both the most recent date and the one a century earlier
+    Both,
+}
+
+#[cfg(feature = "std")]
+#[derive(Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct DecodedData {
+    /// The three letters encoding the last name
+    pub last_name: String,
+    /// The three letters encoding the first name
+    pub first_name: String,
     pub birth_date: NaiveDate,
+    /// The birth date one century earlier when the [`CenturyPolicy`] considers it plausible
+    pub alternative_birth_date: Option<NaiveDate>,
     pub gender: Gender,
-    pub birth_place: String,
-    pub birth_province: String,
+    pub birth_place: DecodedBirthPlace,
+    pub belfiore_code: String,
+    pub place_status: PlaceStatus,
+    /// Positions where a digit was substituted with a letter, empty if it's not an omocode
+    pub omocode_positions: Vec<usize>,
+}
+
+#[cfg(feature = "std")]
+impl DecodedData {
+    pub fn is_omocode(&self) -> bool {
+        !self.omocode_positions.is_empty()
+    }
+}
+
+#[cfg(feature = "std")]
+#[derive(Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(tag = "kind", rename_all = "snake_case"))]
+pub enum DecodedBirthPlace {
+    /// The municipality is the display name, like "Abbadia Lariana"
+    Italy {
+        municipality: String,
+        province: String,
+    },
+    Foreign(Country),
+}
+
+#[cfg(feature = "std")]
+impl DecodedBirthPlace {
+    /// The province with its full name and region, `None` for people born abroad
+    pub fn province(&self) -> Option<Province> {
+        match self {
+            Self::Italy { province, .. } => Province::by_code(province),
+            Self::Foreign(_) => None,
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "snake_case"))]
+pub enum PlaceStatus {
+    Active,
+    Inactive,
+    Foreign,
 }
 
 /// Returns all subsets the elements of an array excepts the empty set
 /// Which amounts to 2^n - 1 sets
+#[cfg(feature = "std")]
 fn all_subsets(array: &[usize]) -> Vec<Vec<usize>> {
     let mut subsets = Vec::new();
     if array.is_empty() {
@@ -57,7 +268,7 @@ fn all_subsets(array: &[usize]) -> Vec<Vec<usize>> {
     subsets
 }
 
-#[cfg(test)]
+#[cfg(all(test, feature = "std"))]
 mod tests {
     use super::*;
 
@@ -77,4 +288,105 @@ mod tests {
         // 2 to the power of 7 = 128, but that includes the empty case so 127
         assert_eq!(all_subsets(&[1, 2, 3, 4, 5, 6, 7]).len(), 127);
     }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn test_serde_subject_round_trip() {
+        let json = r#"{
+            "first_name": "Maria",
+            "last_name": "D'Angelo",
+            "birth_date": "1970-01-01",
+            "gender": "female",
+            "birth_place": { "kind": "italy", "municipality": "Milano", "province": "MI" }
+        }"#;
+
+        let subject: Subject = serde_json::from_str(json).unwrap();
+        assert_eq!(subject.gender, Gender::Female);
+
+        let serialized = serde_json::to_string(&subject).unwrap();
+        assert_eq!(
+            serde_json::from_str::<Subject>(&serialized).unwrap(),
+            subject
+        );
+    }
+
+    #[test]
+    fn test_subject_validates_province() {
+        let subject = |province: &str| {
+            Subject::new(
+                "Giancarlo".parse().unwrap(),
+                "Galan".parse().unwrap(),
+                NaiveDate::from_ymd_opt(1956, 9, 10).unwrap(),
+                Gender::Male,
+                BirthPlace::Italy {
+                    municipality: "Padova".parse().unwrap(),
+                    province: Some(province.parse().unwrap()),
+                },
+            )
+        };
+
+        assert!(subject("PD").is_ok());
+        assert!(subject("FO").is_ok());
+        assert_eq!(subject("XX"), Err(GenerationError::UnknownProvince));
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn test_serde_birth_place_validates_province() {
+        let json = r#"{ "kind": "italy", "municipality": "Padova", "province": "XX" }"#;
+        let error = serde_json::from_str::<BirthPlace>(json).unwrap_err();
+        assert_eq!(
+            error.to_string(),
+            GenerationError::UnknownProvince.to_string()
+        );
+
+        let json = r#"{ "kind": "italy", "municipality": "Padova", "province": null }"#;
+        assert!(serde_json::from_str::<BirthPlace>(json).is_ok());
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn test_serde_belfiore_birth_place() {
+        let json = r#"{ "kind": "belfiore", "code": "f205" }"#;
+        let birth_place: BirthPlace = serde_json::from_str(json).unwrap();
+        assert_eq!(
+            serde_json::to_string(&birth_place).unwrap(),
+            r#"{"kind":"belfiore","code":"F205"}"#
+        );
+
+        let json = r#"{ "kind": "belfiore", "code": "C008" }"#;
+        assert!(serde_json::from_str::<BirthPlace>(json).is_err());
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn test_serde_cfstring_validates() {
+        let json = r#""Mario1""#;
+        assert!(serde_json::from_str::<CFString<String>>(json).is_err());
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn test_serde_codice_fiscale() {
+        let code: CodiceFiscale = serde_json::from_str(r#""RSSMRA70A41F205Z""#).unwrap();
+        assert_eq!(
+            serde_json::to_string(&code).unwrap(),
+            r#""RSSMRA70A41F205Z""#
+        );
+
+        assert!(serde_json::from_str::<CodiceFiscale>(r#""RSSMRA70A41F205A""#).is_err());
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn test_serde_decoded_data_round_trip() {
+        use std::str::FromStr;
+
+        for code in ["RSSMRA70A41F205Z", "RSSMRA70A41Z404U"] {
+            let decoded = CodiceFiscale::from_str(code).unwrap().decode().unwrap();
+            let serialized = serde_json::to_string(&decoded).unwrap();
+            let deserialized = serde_json::from_str::<DecodedData>(&serialized).unwrap();
+            assert_eq!(deserialized, decoded);
+        }
+    }
 }
@@ -0,0 +1,368 @@
+use std::error::Error;
+use std::fs::File;
+use std::io::{self, BufWriter, Write};
+use std::path::PathBuf;
+use std::process::ExitCode;
+use std::str::FromStr;
+
+use clap::{Args, Parser, Subcommand, ValueEnum};
+use serde_json::{json, Value};
+
+use codice_fiscale::batch::{self, BatchError, BatchOptions};
+use codice_fiscale::{
+    AnyCodiceFiscale, BelfioreCode, BirthPlace, CenturyPolicy, CodiceFiscale, DecodedBirthPlace,
+    Gender, NaiveDate, Place, SearchOptions, Subject, ValidationError,
+};
+
+#[derive(Parser)]
+#[command(version, about = "Encode, decode and validate Italian fiscal codes")]
+struct Cli {
+    /// Print the output as JSON
+    #[arg(long, global = true)]
+    json: bool,
+
+    #[command(subcommand)]
+    command: Command,
+}
+
+#[derive(Subcommand)]
+enum Command {
+    /// Encode a codice fiscale from personal data
+    Encode(EncodeArgs),
+    /// Decode the data contained in a codice fiscale
+    Decode {
+        code: String,
+        /// Date used to resolve the birth century, defaults to today
+        #[arg(long)]
+        reference_date: Option<NaiveDate>,
+    },
+    /// Check a personal or numeric codice fiscale, suggesting corrections for typos
+    Validate { code: String },
+    /// List every omocode of a codice fiscale
+    Omocodes { code: String },
+    /// Replace the omocode letters of a codice fiscale with digits
+    Normalize { code: String },
+    /// Check every row of a CSV file and write it back with the decoded data and the errors
+    Batch(BatchArgs),
+    /// Look up places and their Belfiore codes
+    Places {
+        #[command(subcommand)]
+        command: PlacesCommand,
+    },
+}
+
+#[derive(Subcommand)]
+enum PlacesCommand {
+    /// Find the places whose name matches the query, allowing typos and abbreviations like S.
+    Search {
+        query: String,
+        #[arg(long)]
+        province: Option<String>,
+        #[arg(long, default_value_t = 10)]
+        limit: usize,
+    },
+}
+
+#[derive(Args)]
+struct EncodeArgs {
+    #[arg(long)]
+    first_name: String,
+    #[arg(long)]
+    last_name: String,
+    /// In the YYYY-MM-DD format
+    #[arg(long)]
+    birth_date: NaiveDate,
+    #[arg(long, value_enum)]
+    gender: GenderArg,
+    /// Municipality of birth, for people born in Italy
+    #[arg(long, required_unless_present_any = ["country", "belfiore_code"])]
+    place: Option<String>,
+    /// Can be left out if no other municipality has ever had the same name
+    #[arg(long, requires = "place")]
+    province: Option<String>,
+    /// Name or ISO code of the country of birth, for people born abroad
+    #[arg(long, conflicts_with_all = ["place", "province"])]
+    country: Option<String>,
+    /// Cadastral code of the place of birth, used instead of its name
+    #[arg(long, conflicts_with_all = ["place", "province", "country"])]
+    belfiore_code: Option<BelfioreCode>,
+}
+
+#[derive(Args)]
+struct BatchArgs {
+    /// CSV file with a header row, `-` reads from the standard input
+    input: PathBuf,
+    /// Where to write the enriched CSV
+    #[arg(long, short)]
+    output: PathBuf,
+    /// Name of the column containing the codes
+    #[arg(long, default_value = "codice_fiscale")]
+    code_column: String,
+    #[arg(long, default_value_t = ',')]
+    delimiter: char,
+    /// Date used to resolve the birth century, defaults to today
+    #[arg(long)]
+    reference_date: Option<NaiveDate>,
+}
+
+#[derive(Clone, Copy, ValueEnum)]
+enum GenderArg {
+    #[value(alias = "male")]
+    M,
+    #[value(alias = "female")]
+    F,
+}
+
+/// Exit code for codes that are invalid or data that can't be encoded
+const EXIT_INVALID: u8 = 1;
+/// Exit code for files that can't be read or written, 2 is used by clap for usage errors
+const EXIT_IO: u8 = 3;
+
+fn main() -> ExitCode {
+    let cli = Cli::parse();
+
+    let (printed, exit_code) = match run(cli.command) {
+        Ok(output) => (
+            print_output(&mut io::stdout().lock(), &output, cli.json),
+            ExitCode::SUCCESS,
+        ),
+        Err(Failure {
+            message,
+            details,
+            exit_code,
+        }) => {
+            let printed = if cli.json {
+                let mut output = json!({ "error": message });
+                if let Some(details) = details {
+                    output["details"] = details;
+                }
+                print_output(&mut io::stdout().lock(), &output, true)
+            } else {
+                let mut stderr = io::stderr().lock();
+                writeln!(stderr, "error: {message}").and_then(|()| match details {
+                    Some(details) => print_human(&mut stderr, &details, 0),
+                    None => Ok(()),
+                })
+            };
+            (printed, ExitCode::from(exit_code))
+        }
+    };
+
+    match printed {
+        // NOTE: Readers like `head` close the pipe once they have read enough
+        Err(error) if error.kind() != io::ErrorKind::BrokenPipe => {
+            eprintln!("error: {error}");
+            ExitCode::from(EXIT_IO)
+        }
+        _ => exit_code,
+    }
+}
+
+struct Failure {
+    message: String,
+    details: Option<Value>,
+    exit_code: u8,
+}
+
+impl Failure {
+    fn io(error: impl Error) -> Self {
+        Self {
+            exit_code: EXIT_IO,
+            ..error.into()
+        }
+    }
+}
+
+impl<E: Error> From<E> for Failure {
+    fn from(error: E) -> Self {
+        Self {
+            message: error.to_string(),
+            details: None,
+            exit_code: EXIT_INVALID,
+        }
+    }
+}
+
+fn run(command: Command) -> Result<Value, Failure> {
+    match command {
+        Command::Encode(args) => encode(args),
+        Command::Decode {
+            code,
+            reference_date,
+        } => {
+            let reference = reference_date.unwrap_or_else(|| chrono::Utc::now().date_naive());
+            let decoded = CodiceFiscale::from_str(&code)?
+                .decode_with(reference, CenturyPolicy::PreferPast)?;
+
+            let mut output = serde_json::to_value(&decoded).unwrap();
+            if let DecodedBirthPlace::Foreign(country) = decoded.birth_place {
+                output["birth_place"]["name"] = country.display_name().into();
+            }
+            Ok(output)
+        }
+        Command::Validate { code } => validate(&code),
+        Command::Batch(args) => batch(args),
+        Command::Omocodes { code } => {
+            let omocodes = CodiceFiscale::parse_strict(&code)?.all_omocodes()?;
+            Ok(serde_json::to_value(omocodes).unwrap())
+        }
+        Command::Normalize { code } => {
+            let code = CodiceFiscale::parse_strict(&code)?.normalize()?;
+            Ok(json!(code.get()))
+        }
+        Command::Places {
+            command:
+                PlacesCommand::Search {
+                    query,
+                    province,
+                    limit,
+                },
+        } => {
+            let options = SearchOptions {
+                province,
+                limit,
+                ..Default::default()
+            };
+            Ok(serde_json::to_value(Place::search(&query, &options)).unwrap())
+        }
+    }
+}
+
+fn encode(args: EncodeArgs) -> Result<Value, Failure> {
+    let birth_place = match (args.place, args.province, args.country, args.belfiore_code) {
+        (_, _, _, Some(code)) => BirthPlace::Belfiore { code },
+        (Some(place), province, _, _) => BirthPlace::Italy {
+            municipality: place.parse()?,
+            province: province.as_deref().map(str::parse).transpose()?,
+        },
+        (_, _, Some(country), _) => BirthPlace::Foreign {
+            country: country.parse()?,
+        },
+        _ => unreachable!("clap requires either a place, a country or a belfiore code"),
+    };
+
+    let subject = Subject::new(
+        args.first_name.parse()?,
+        args.last_name.parse()?,
+        args.birth_date,
+        match args.gender {
+            GenderArg::M => Gender::Male,
+            GenderArg::F => Gender::Female,
+        },
+        birth_place,
+    )?;
+
+    let code = CodiceFiscale::encode(&subject)?;
+    if let Some(change) = subject.province_change() {
+        eprintln!(
+            "note: on {} the municipality was in the province of {}, not {}",
+            subject.birth_date, change.recorded, change.given
+        );
+    }
+    Ok(json!(code.get()))
+}
+
+fn batch(args: BatchArgs) -> Result<Value, Failure> {
+    if !args.delimiter.is_ascii() {
+        return Err(Failure {
+            message: "the delimiter must be an ascii character".to_owned(),
+            details: None,
+            exit_code: EXIT_INVALID,
+        });
+    }
+
+    let mut options = BatchOptions {
+        code_column: args.code_column,
+        delimiter: args.delimiter as u8,
+        ..Default::default()
+    };
+    if let Some(reference_date) = args.reference_date {
+        options.reference_date = reference_date;
+    }
+
+    let output = BufWriter::new(File::create(&args.output).map_err(Failure::io)?);
+    let summary = if args.input.as_os_str() == "-" {
+        batch::enrich(io::stdin().lock(), output, &options)
+    } else {
+        batch::enrich(
+            File::open(&args.input).map_err(Failure::io)?,
+            output,
+            &options,
+        )
+    };
+    let summary = summary.map_err(|error| match error {
+        BatchError::Csv(ref csv) if csv.is_io_error() => Failure::io(error),
+        error => error.into(),
+    })?;
+
+    Ok(serde_json::to_value(summary).unwrap())
+}
+
+fn validate(code: &str) -> Result<Value, Failure> {
+    let kind = match AnyCodiceFiscale::from_str(code) {
+        Ok(AnyCodiceFiscale::Numeric(_)) => "numeric",
+        Ok(_) => match CodiceFiscale::parse_strict(code) {
+            Ok(_) => "personal",
+            Err(error) => return Err(invalid_personal_code(code, error)),
+        },
+        Err(error) => return Err(error.into()),
+    };
+
+    Ok(json!({ "valid": true, "kind": kind }))
+}
+
+fn invalid_personal_code(code: &str, error: ValidationError) -> Failure {
+    let suggestions = CodiceFiscale::from_str(code)
+        .map(|c| c.suggest_corrections())
+        .unwrap_or_default();
+
+    Failure {
+        message: error.to_string(),
+        details: (!suggestions.is_empty())
+            .then(|| json!({ "suggestions": serde_json::to_value(suggestions).unwrap() })),
+        exit_code: EXIT_INVALID,
+    }
+}
+
+fn print_output(out: &mut impl Write, output: &Value, json: bool) -> io::Result<()> {
+    if json {
+        writeln!(out, "{output}")?;
+    } else {
+        print_human(out, output, 0)?;
+    }
+    out.flush()
+}
+
+/// Prints strings as they are, arrays one element at a time and objects as `key: value` lines,
+/// nested values are indented
+fn print_human(out: &mut impl Write, output: &Value, depth: usize) -> io::Result<()> {
+    let indent = "  ".repeat(depth);
+
+    match output {
+        Value::String(string) => writeln!(out, "{indent}{string}")?,
+        Value::Array(values) => {
+            for (i, value) in values.iter().enumerate() {
+                if i > 0 && value.is_object() {
+                    writeln!(out)?;
+                }
+                print_human(out, value, depth)?;
+            }
+        }
+        Value::Object(map) => {
+            for (key, value) in map {
+                match value {
+                    Value::Null => {}
+                    Value::String(string) => writeln!(out, "{indent}{key}: {string}")?,
+                    Value::Array(values) if values.is_empty() => {}
+                    Value::Object(_) | Value::Array(_) => {
+                        writeln!(out, "{indent}{key}:")?;
+                        print_human(out, value, depth + 1)?;
+                    }
+                    other => writeln!(out, "{indent}{key}: {other}")?,
+                }
+            }
+        }
+        other => writeln!(out, "{indent}{other}")?,
+    }
+    Ok(())
+}
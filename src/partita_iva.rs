@@ -0,0 +1,298 @@
+use std::str::FromStr;
+
+use crate::{CodiceFiscale, GenerationError, ValidationError};
+
+/// Province abbreviations indexed by office code - 1, the office codes follow the ISTAT
+/// numbering of the provinces
+const OFFICE_PROVINCES: [&str; 111] = [
+    "TO", "VC", "NO", "CN", "AT", "AL", "AO", "IM", "SV", "GE", "SP", "VA", "CO", "SO", "MI", "BG",
+    "BS", "PV", "CR", "MN", "BZ", "TN", "VR", "VI", "BL", "TV", "VE", "PD", "RO", "UD", "GO", "TS",
+    "PC", "PR", "RE", "MO", "BO", "FE", "RA", "FC", "PU", "AN", "MC", "AP", "MS", "LU", "PT", "FI",
+    "LI", "PI", "AR", "SI", "GR", "PG", "TR", "VT", "RI", "RM", "LT", "FR", "CE", "BN", "NA", "AV",
+    "SA", "AQ", "TE", "PE", "CH", "CB", "FG", "BA", "TA", "BR", "LE", "PZ", "MT", "CS", "CZ", "RC",
+    "TP", "PA", "ME", "AG", "CL", "EN", "CT", "RG", "SR", "SS", "NU", "CA", "PN", "IS", "OR", "BI",
+    "LC", "LO", "RN", "PO", "KR", "VV", "VB", "OT", "OG", "VS", "CI", "MB", "FM", "BT", "SU",
+];
+
+/// The 11 digit numeric codice fiscale used by companies and other entities, which is the same
+/// as their partita IVA: a 7 digit serial number, a 3 digit office code and a check digit
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PartitaIva(String);
+
+impl PartitaIva {
+    pub fn generate(serial: u32, office_code: u16) -> Result<Self, GenerationError> {
+        if serial > 9_999_999 || office_code == 0 || office_code > 999 {
+            return Err(GenerationError::NumberOutOfRange);
+        }
+
+        let mut output = format!("{serial:07}{office_code:03}");
+        output.push(Self::compute_check_digit(&output)?);
+
+        Ok(Self(output))
+    }
+
+    /// Luhn check digit of the first 10 digits
+    pub fn compute_check_digit(partial: &str) -> Result<char, GenerationError> {
+        if partial.len() != 10 {
+            return Err(GenerationError::IncorrectPartitaIvaChecksumInputLength);
+        }
+        if !partial.bytes().all(|b| b.is_ascii_digit()) {
+            return Err(GenerationError::NonNumericChecksumInput);
+        }
+
+        let mut sum = 0;
+        for (i, b) in partial.bytes().enumerate() {
+            let mut digit = b - b'0';
+            // NOTE: The digits in even positions are doubled, counting from 1
+            if i % 2 == 1 {
+                digit *= 2;
+                if digit > 9 {
+                    digit -= 9;
+                }
+            }
+            sum += digit as u32;
+        }
+
+        Ok((((10 - sum % 10) % 10) as u8 + b'0') as char)
+    }
+
+    pub fn get(&self) -> &str {
+        &self.0
+    }
+
+    pub fn serial(&self) -> u32 {
+        self.0[0..7].parse().unwrap()
+    }
+
+    pub fn office_code(&self) -> u16 {
+        self.0[7..10].parse().unwrap()
+    }
+
+    /// The province of the office that issued the number, `None` for the special office codes
+    /// that aren't tied to a province
+    pub fn province(&self) -> Option<&'static str> {
+        let index = (self.office_code() as usize).checked_sub(1)?;
+        OFFICE_PROVINCES.get(index).copied()
+    }
+
+    pub fn kind(&self) -> NumericKind {
+        match self.0.as_bytes()[0] {
+            b'8' | b'9' => NumericKind::NonCommercialEntity,
+            _ => NumericKind::PartitaIvaOrProvisional,
+        }
+    }
+}
+
+/// What kind of holder a numeric code can belong to, as far as its format tells
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub enum NumericKind {
+    /// Codes starting with 8 or 9 are assigned to associations and other non-commercial
+    /// entities, they are neither a partita IVA nor a code for a person
+    NonCommercialEntity,
+    /// The partita IVA of a company or a sole trader and the provisional codice fiscale of
+    /// a person share the same format, only the holder can tell them apart
+    PartitaIvaOrProvisional,
+}
+
+/// The temporary numeric codice fiscale given to a person that is still waiting for their
+/// definitive one, it has the same format as a [`PartitaIva`]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ProvisionalCodiceFiscale(PartitaIva);
+
+impl ProvisionalCodiceFiscale {
+    pub fn get(&self) -> &str {
+        self.0.get()
+    }
+
+    pub fn office_code(&self) -> u16 {
+        self.0.office_code()
+    }
+
+    pub fn province(&self) -> Option<&'static str> {
+        self.0.province()
+    }
+}
+
+impl TryFrom<PartitaIva> for ProvisionalCodiceFiscale {
+    type Error = ValidationError;
+
+    fn try_from(value: PartitaIva) -> Result<Self, Self::Error> {
+        match value.kind() {
+            NumericKind::NonCommercialEntity => Err(ValidationError::NotANaturalPerson),
+            NumericKind::PartitaIvaOrProvisional => Ok(Self(value)),
+        }
+    }
+}
+
+impl FromStr for ProvisionalCodiceFiscale {
+    type Err = ValidationError;
+
+    fn from_str(string: &str) -> Result<Self, Self::Err> {
+        PartitaIva::from_str(string)?.try_into()
+    }
+}
+
+impl FromStr for PartitaIva {
+    type Err = ValidationError;
+
+    fn from_str(string: &str) -> Result<Self, Self::Err> {
+        if string.len() != 11 {
+            return Err(ValidationError::IncorrectLength(11));
+        }
+        if !string.bytes().all(|b| b.is_ascii_digit()) {
+            return Err(ValidationError::NonNumeric);
+        }
+
+        let check_digit = Self::compute_check_digit(&string[0..10]).unwrap();
+        if !string.ends_with(check_digit) {
+            return Err(ValidationError::InvalidChecksum);
+        }
+
+        Ok(Self(string.to_owned()))
+    }
+}
+
+/// Either kind of codice fiscale, the personal 16 character one or the numeric one
+#[derive(Debug, Clone)]
+pub enum AnyCodiceFiscale {
+    Personal(CodiceFiscale),
+    Numeric(PartitaIva),
+    Provisional(ProvisionalCodiceFiscale),
+}
+
+impl AnyCodiceFiscale {
+    /// Parses the code of someone known to be a natural person, so numeric codes are
+    /// treated as provisional codes instead of a partita IVA
+    pub fn parse_person(string: &str) -> Result<Self, ValidationError> {
+        match Self::from_str(string)? {
+            Self::Numeric(code) => code.try_into().map(Self::Provisional),
+            other => Ok(other),
+        }
+    }
+}
+
+impl FromStr for AnyCodiceFiscale {
+    type Err = ValidationError;
+
+    /// Strings made only of digits are parsed as numeric codes, anything else as personal ones
+    fn from_str(string: &str) -> Result<Self, Self::Err> {
+        if !string.is_empty() && string.bytes().all(|b| b.is_ascii_digit()) {
+            return PartitaIva::from_str(string).map(Self::Numeric);
+        }
+
+        CodiceFiscale::from_str(string).map(Self::Personal)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_check_digit() {
+        assert_eq!(PartitaIva::compute_check_digit("0074311015"), Ok('7'));
+        assert_eq!(PartitaIva::compute_check_digit("1234567058"), Ok('7'));
+        let error = PartitaIva::compute_check_digit("123456705").unwrap_err();
+        assert_eq!(
+            error,
+            GenerationError::IncorrectPartitaIvaChecksumInputLength
+        );
+        assert!(!CodiceFiscale::compute_checksum("123456705")
+            .unwrap_err()
+            .to_string()
+            .contains("partita IVA"));
+    }
+
+    #[test]
+    fn test_parse() {
+        let code = PartitaIva::from_str("00743110157").unwrap();
+        assert_eq!(code.serial(), 74311);
+        assert_eq!(code.office_code(), 15);
+        assert_eq!(code.province(), Some("MI"));
+    }
+
+    #[test]
+    fn test_parse_errors() {
+        assert_eq!(
+            PartitaIva::from_str("00743110158"),
+            Err(ValidationError::InvalidChecksum)
+        );
+        assert_eq!(
+            PartitaIva::from_str("0074311015A"),
+            Err(ValidationError::NonNumeric)
+        );
+        assert_eq!(
+            PartitaIva::from_str("0074311015"),
+            Err(ValidationError::IncorrectLength(11))
+        );
+    }
+
+    #[test]
+    fn test_generate() {
+        let code = PartitaIva::generate(74311, 15).unwrap();
+        assert_eq!(code.get(), "00743110157");
+        assert_eq!(
+            PartitaIva::generate(10_000_000, 15),
+            Err(GenerationError::NumberOutOfRange)
+        );
+    }
+
+    #[test]
+    fn test_special_office_has_no_province() {
+        let code = PartitaIva::generate(1, 999).unwrap();
+        assert_eq!(code.province(), None);
+    }
+
+    #[test]
+    fn test_any_codice_fiscale() {
+        assert!(matches!(
+            AnyCodiceFiscale::from_str("00743110157"),
+            Ok(AnyCodiceFiscale::Numeric(_))
+        ));
+        assert!(matches!(
+            AnyCodiceFiscale::from_str("RSSMRA70A41F205Z"),
+            Ok(AnyCodiceFiscale::Personal(_))
+        ));
+        assert!(AnyCodiceFiscale::from_str("007431101").is_err());
+    }
+
+    #[test]
+    fn test_kind() {
+        let code = PartitaIva::from_str("00743110157").unwrap();
+        assert_eq!(code.kind(), NumericKind::PartitaIvaOrProvisional);
+
+        let code = PartitaIva::generate(8_000_001, 58).unwrap();
+        assert_eq!(code.kind(), NumericKind::NonCommercialEntity);
+    }
+
+    #[test]
+    fn test_provisional() {
+        let code = ProvisionalCodiceFiscale::from_str("00743110157").unwrap();
+        assert_eq!(code.province(), Some("MI"));
+
+        let entity = PartitaIva::generate(9_000_001, 58).unwrap();
+        assert_eq!(
+            ProvisionalCodiceFiscale::from_str(entity.get()),
+            Err(ValidationError::NotANaturalPerson)
+        );
+        assert_eq!(
+            ProvisionalCodiceFiscale::from_str("00743110158"),
+            Err(ValidationError::InvalidChecksum)
+        );
+    }
+
+    #[test]
+    fn test_parse_person() {
+        assert!(matches!(
+            AnyCodiceFiscale::parse_person("00743110157"),
+            Ok(AnyCodiceFiscale::Provisional(_))
+        ));
+        assert!(matches!(
+            AnyCodiceFiscale::parse_person("RSSMRA70A41F205Z"),
+            Ok(AnyCodiceFiscale::Personal(_))
+        ));
+
+        let entity = PartitaIva::generate(8_000_001, 58).unwrap();
+        assert!(AnyCodiceFiscale::parse_person(entity.get()).is_err());
+    }
+}
@@ -0,0 +1,355 @@
+use chrono::NaiveDate;
+
+use crate::Province;
+
+include!(concat!(env!("OUT_DIR"), "/codegen.rs"));
+
+/// A municipality or foreign state as recorded in the Belfiore tables
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct Place {
+    pub code: &'static str,
+    pub name: &'static str,
+    pub province: &'static str,
+    pub active: bool,
+    /// First day the place existed, `None` if unknown
+    pub valid_from: Option<NaiveDate>,
+    /// First day the place stopped existing, `None` if it still exists or is unknown
+    pub valid_to: Option<NaiveDate>,
+}
+
+/// A municipality found by [`Place::resolve`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct ResolvedPlace {
+    /// The record of the municipality that was valid on the date
+    pub place: &'static Place,
+    /// Set when the municipality is recorded in a different province than the given one
+    pub province_change: Option<ProvinceChange>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct ProvinceChange {
+    pub given: &'static str,
+    pub recorded: &'static str,
+}
+
+impl Place {
+    /// Returns all the places that have ever had this name in this province,
+    /// active ones first followed by the most recent inactive ones
+    pub fn by_name(name: &str, province: &str) -> &'static [Place] {
+        let mut buffer = [0; MAX_NAME_KEY_LEN];
+        name_key(name, Some(province), &mut buffer)
+            .and_then(|key| PLACES_BY_NAME.get(key))
+            .copied()
+            .unwrap_or_default()
+    }
+
+    /// Returns every Italian municipality that has ever had this name in any province, active
+    /// ones first followed by the most recent inactive ones
+    pub fn by_municipality(name: &str) -> &'static [Place] {
+        let mut buffer = [0; MAX_NAME_KEY_LEN];
+        name_key(name, None, &mut buffer)
+            .and_then(|key| PLACES_BY_MUNICIPALITY.get(key))
+            .copied()
+            .unwrap_or_default()
+    }
+
+    /// Finds the municipality with this name that existed on `date`, the province can be the
+    /// current one or any province it has been recorded in, like "MI" for Abbadia Cerreto
+    /// that is now in the province of Lodi
+    pub fn resolve(name: &str, province: &str, date: NaiveDate) -> Option<ResolvedPlace> {
+        let given = Province::by_code(province)?;
+
+        let places = Self::by_municipality(name);
+        let place = places.iter().filter(|p| p.is_valid_on(date)).find(|p| {
+            places
+                .iter()
+                .any(|other| other.code == p.code && other.province == given.code)
+        })?;
+
+        let province_change = (place.province != given.code).then_some(ProvinceChange {
+            given: given.code,
+            recorded: place.province,
+        });
+        Some(ResolvedPlace {
+            place,
+            province_change,
+        })
+    }
+
+    /// Iterates over every record of the tables, grouped by code
+    pub fn all() -> impl Iterator<Item = &'static Place> {
+        PLACES_BY_CODE.values().flat_map(|places| places.iter())
+    }
+
+    /// Returns every place that has ever used this Belfiore code, active ones first followed
+    /// by the most recent inactive ones, places with the same period are sorted by name
+    pub fn by_code(code: &str) -> &'static [Place] {
+        let mut buffer = [0; 4];
+        ascii_uppercase(code, &mut buffer)
+            .and_then(|code| PLACES_BY_CODE.get(code))
+            .copied()
+            .unwrap_or_default()
+    }
+
+    /// Returns the first of the places that existed on the given date
+    pub fn valid_on(places: &[Place], date: NaiveDate) -> Option<&Place> {
+        places.iter().find(|p| p.is_valid_on(date))
+    }
+
+    /// The name with capitalized words, like "Reggio nell Emilia" or "San Giovanni in Fiore".
+    /// Apostrophes and accents are not part of the tables so they can't be restored
+    pub fn display_name(&self) -> String {
+        display_name(self.name)
+    }
+
+    /// Unknown bounds are considered open so places without dates are always valid
+    pub fn is_valid_on(&self, date: NaiveDate) -> bool {
+        let started = self.valid_from.is_none_or(|from| from <= date);
+        let not_ended = self.valid_to.is_none_or(|to| date < to);
+
+        started && not_ended
+    }
+}
+
+/// Writes the key of [`PLACES_BY_NAME`] in the buffer: the name in the format used by the
+/// tables, lowercase words separated by a single hyphen like in "sant-elena", a comma and the
+/// uppercase province. Without a province it's the key of [`PLACES_BY_MUNICIPALITY`], just
+/// the name. Returns `None` if the key doesn't fit, so no place can have it
+fn name_key<'a>(name: &str, province: Option<&str>, buffer: &'a mut [u8]) -> Option<&'a str> {
+    let mut len = 0;
+    let mut push = |b: u8| {
+        *buffer.get_mut(len)? = b;
+        len += 1;
+        Some(())
+    };
+
+    let words = name
+        .split(|c: char| !c.is_ascii_alphanumeric())
+        .filter(|word| !word.is_empty());
+    for (i, word) in words.enumerate() {
+        if i > 0 {
+            push(b'-')?;
+        }
+        for b in word.bytes() {
+            push(b.to_ascii_lowercase())?;
+        }
+    }
+
+    if let Some(province) = province {
+        push(b',')?;
+        for b in province.bytes() {
+            push(b.to_ascii_uppercase())?;
+        }
+    }
+
+    std::str::from_utf8(&buffer[..len]).ok()
+}
+
+/// Uppercases an ascii string into the buffer, `None` if it's longer than the buffer
+pub(crate) fn ascii_uppercase<'a>(string: &str, buffer: &'a mut [u8]) -> Option<&'a str> {
+    let buffer = buffer.get_mut(..string.len())?;
+    buffer.copy_from_slice(string.as_bytes());
+    buffer.make_ascii_uppercase();
+
+    std::str::from_utf8(buffer).ok()
+}
+
+/// Articles and prepositions that stay lowercase in place names unless they come first
+pub(crate) const LOWERCASE_WORDS: [&str; 24] = [
+    "a", "al", "all", "alla", "alle", "d", "da", "dal", "de", "dei", "del", "della", "delle",
+    "dell", "di", "e", "ed", "in", "nel", "nell", "nella", "sul", "sull", "sulla",
+];
+
+pub(crate) fn display_name(slug: &str) -> String {
+    slug.split(['-', ' '])
+        .filter(|word| !word.is_empty())
+        .enumerate()
+        .map(|(i, word)| {
+            if i > 0 && LOWERCASE_WORDS.contains(&word) {
+                return word.to_owned();
+            }
+
+            let mut chars = word.chars();
+            chars
+                .next()
+                .map(|first| first.to_ascii_uppercase().to_string() + chars.as_str())
+                .unwrap_or_default()
+        })
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn date(year: i32, month: u32, day: u32) -> NaiveDate {
+        NaiveDate::from_ymd_opt(year, month, day).unwrap()
+    }
+
+    fn place(
+        code: &'static str,
+        valid_from: Option<NaiveDate>,
+        valid_to: Option<NaiveDate>,
+    ) -> Place {
+        Place {
+            code,
+            name: "test",
+            province: "TS",
+            active: valid_to.is_none(),
+            valid_from,
+            valid_to,
+        }
+    }
+
+    #[test]
+    fn test_valid_on_without_dates() {
+        let p = place("A001", None, None);
+        assert!(p.is_valid_on(date(1800, 1, 1)));
+        assert!(p.is_valid_on(date(2100, 1, 1)));
+    }
+
+    #[test]
+    fn test_valid_on_bounds() {
+        let p = place("A001", Some(date(1950, 1, 1)), Some(date(2014, 1, 1)));
+        assert!(!p.is_valid_on(date(1949, 12, 31)));
+        assert!(p.is_valid_on(date(1950, 1, 1)));
+        assert!(p.is_valid_on(date(2013, 12, 31)));
+        assert!(!p.is_valid_on(date(2014, 1, 1)));
+    }
+
+    #[test]
+    fn test_valid_on_picks_place_of_the_period() {
+        let places = [
+            place("M001", Some(date(2014, 1, 1)), None),
+            place("A001", Some(date(1861, 3, 17)), Some(date(2014, 1, 1))),
+        ];
+
+        let code = |d| Place::valid_on(&places, d).map(|p| p.code);
+        assert_eq!(code(date(1950, 6, 1)), Some("A001"));
+        assert_eq!(code(date(2020, 6, 1)), Some("M001"));
+        assert_eq!(code(date(1850, 6, 1)), None);
+    }
+
+    #[test]
+    fn test_valid_on_merged_municipality() {
+        // Longarone was merged with Castellavazzo into a new municipality on 2014-02-22
+        let places = Place::by_name("Longarone", "BL");
+        let code = |d| Place::valid_on(places, d).map(|p| p.code);
+        assert_eq!(code(date(1956, 9, 10)), Some("E672"));
+        assert_eq!(code(date(2014, 2, 21)), Some("E672"));
+        assert_eq!(code(date(2014, 2, 22)), Some("M342"));
+        assert_eq!(code(date(2020, 1, 1)), Some("M342"));
+    }
+
+    #[test]
+    fn test_by_code_all_candidates() {
+        let places = Place::by_code("a001");
+        let names = places
+            .iter()
+            .map(|p| (p.name, p.active))
+            .collect::<Vec<_>>();
+        assert_eq!(names, vec![("abano-terme", true), ("abano", false)]);
+    }
+
+    #[test]
+    fn test_by_code_name_variants() {
+        let places = Place::by_code("Z532");
+        let names = places.iter().map(|p| p.name).collect::<Vec<_>>();
+        assert_eq!(names, vec!["antigua-and-barbuda", "antigua-e-barbuda"]);
+        assert!(places.iter().all(|p| p.province == "EE"));
+    }
+
+    #[test]
+    fn test_all() {
+        let lines = include_str!("../data/active_places.csv").lines().count();
+        assert!(Place::all().count() >= lines);
+    }
+
+    #[test]
+    fn test_by_code_unknown() {
+        assert!(Place::by_code("C008").is_empty());
+    }
+
+    #[test]
+    fn test_by_name_punctuation() {
+        let places = Place::by_name("Sant'Elena", "EE");
+        assert_eq!(places[0].code, "Z340");
+
+        let places = Place::by_name("Reggio  nell'Emilia", "RE");
+        assert!(!places.is_empty());
+    }
+
+    #[test]
+    fn test_by_municipality() {
+        let provinces = Place::by_municipality("Monza")
+            .iter()
+            .map(|p| (p.code, p.province, p.active))
+            .collect::<Vec<_>>();
+        assert_eq!(provinces[0], ("F704", "MB", true));
+        assert!(provinces[1..].iter().all(|p| *p == ("F704", "MI", false)));
+
+        assert!(Place::by_municipality("Germania").is_empty());
+    }
+
+    #[test]
+    fn test_resolve_historic_province() {
+        // Abbadia Cerreto was in the province of Milano until Lodi was created in 1992
+        let resolved = Place::resolve("Abbadia Cerreto", "mi", date(1980, 1, 1)).unwrap();
+        assert_eq!(resolved.place.code, "A004");
+        assert_eq!(resolved.place.province, "MI");
+        assert_eq!(resolved.province_change, None);
+
+        let resolved = Place::resolve("Abbadia Cerreto", "mi", date(2000, 1, 1)).unwrap();
+        assert_eq!(resolved.place.code, "A004");
+        assert_eq!(
+            resolved.province_change,
+            Some(ProvinceChange {
+                given: "MI",
+                recorded: "LO"
+            })
+        );
+
+        let resolved = Place::resolve("Abbadia Cerreto", "LO", date(2000, 1, 1)).unwrap();
+        assert_eq!(resolved.province_change, None);
+
+        // Carbonia was in the province of Cagliari and then Carbonia-Iglesias
+        let resolved = Place::resolve("Carbonia", "CA", date(1980, 1, 1)).unwrap();
+        assert_eq!(resolved.place.code, "B745");
+    }
+
+    #[test]
+    fn test_resolve_other_province() {
+        let date = date(1980, 1, 1);
+
+        let resolved = Place::resolve("Arbus", "VS", date).unwrap();
+        assert_eq!(resolved.place.code, "A359");
+        assert_eq!(resolved.province_change.unwrap().recorded, "SU");
+
+        // Arbus was never part of Carbonia-Iglesias, even if both ended up in Sud Sardegna
+        assert_eq!(Place::resolve("Arbus", "CI", date), None);
+        assert_eq!(Place::resolve("Arbus", "XX", date), None);
+    }
+
+    #[test]
+    fn test_display_name() {
+        assert_eq!(display_name("abbadia-lariana"), "Abbadia Lariana");
+        assert_eq!(display_name("reggio-nell-emilia"), "Reggio nell Emilia");
+        assert_eq!(
+            display_name("san-giovanni-in-fiore"),
+            "San Giovanni in Fiore"
+        );
+        assert_eq!(display_name("e"), "E");
+    }
+
+    #[test]
+    fn test_by_name_active_first() {
+        let places = Place::by_name("Abbadia Lariana", "lc");
+        assert!(!places.is_empty());
+        assert!(places[0].active);
+        assert_eq!(places[0].code, "A005");
+    }
+}
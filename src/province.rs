@@ -0,0 +1,195 @@
+use crate::place::{ascii_uppercase, Place};
+use crate::search::normalize;
+
+include!(concat!(env!("OUT_DIR"), "/provinces.rs"));
+
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "snake_case"))]
+pub enum Region {
+    Abruzzo,
+    Basilicata,
+    Calabria,
+    Campania,
+    EmiliaRomagna,
+    FriuliVeneziaGiulia,
+    Lazio,
+    Liguria,
+    Lombardia,
+    Marche,
+    Molise,
+    Piemonte,
+    Puglia,
+    Sardegna,
+    Sicilia,
+    Toscana,
+    TrentinoAltoAdige,
+    Umbria,
+    ValleDAosta,
+    Veneto,
+    /// The provinces of Fiume, Pola and Zara that were ceded to Yugoslavia in 1947
+    VeneziaGiulia,
+}
+
+impl Region {
+    pub fn name(&self) -> &'static str {
+        match self {
+            Self::Abruzzo => "Abruzzo",
+            Self::Basilicata => "Basilicata",
+            Self::Calabria => "Calabria",
+            Self::Campania => "Campania",
+            Self::EmiliaRomagna => "Emilia-Romagna",
+            Self::FriuliVeneziaGiulia => "Friuli-Venezia Giulia",
+            Self::Lazio => "Lazio",
+            Self::Liguria => "Liguria",
+            Self::Lombardia => "Lombardia",
+            Self::Marche => "Marche",
+            Self::Molise => "Molise",
+            Self::Piemonte => "Piemonte",
+            Self::Puglia => "Puglia",
+            Self::Sardegna => "Sardegna",
+            Self::Sicilia => "Sicilia",
+            Self::Toscana => "Toscana",
+            Self::TrentinoAltoAdige => "Trentino-Alto Adige",
+            Self::Umbria => "Umbria",
+            Self::ValleDAosta => "Valle d'Aosta",
+            Self::Veneto => "Veneto",
+            Self::VeneziaGiulia => "Venezia Giulia",
+        }
+    }
+
+    /// The provinces of the region, including the ones that don't exist anymore
+    pub fn provinces(self) -> impl Iterator<Item = Province> {
+        Province::all().filter(move |province| province.region == self)
+    }
+}
+
+/// A province, or an equivalent body like a metropolitan city, that appears in the tables
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct Province {
+    /// The two letter sigla, like "PD"
+    pub code: &'static str,
+    pub name: &'static str,
+    pub region: Region,
+    pub active: bool,
+    /// The province that took over most of the municipalities of a suppressed or renamed one,
+    /// like "FC" for "FO". `None` for active provinces and the ones that were ceded
+    pub successor: Option<&'static str>,
+}
+
+impl Province {
+    pub fn by_code(code: &str) -> Option<Self> {
+        let mut buffer = [0; 2];
+        ascii_uppercase(code, &mut buffer)
+            .and_then(|code| PROVINCES_BY_CODE.get(code))
+            .copied()
+    }
+
+    /// Accents, punctuation, articles and prepositions are ignored, so "Monza e Brianza"
+    /// finds "Monza e della Brianza". Active provinces are preferred over historic ones with
+    /// the same name, like "PU" over "PS" for "Pesaro e Urbino"
+    pub fn by_name(name: &str) -> Option<Self> {
+        let name = normalize(name);
+        Self::all()
+            .filter(|province| normalize(province.name) == name)
+            .max_by_key(|province| province.active)
+    }
+
+    /// Iterates over every province in no particular order
+    pub fn all() -> impl Iterator<Item = Self> {
+        PROVINCES_BY_CODE.values().copied()
+    }
+
+    /// The province that this one became, following renames and suppressions until an
+    /// active one or one without successor
+    pub fn current(&self) -> Self {
+        let mut province = *self;
+        while let Some(successor) = province.successor.and_then(Self::by_code) {
+            province = successor;
+        }
+        province
+    }
+
+    /// Every municipality that has ever been recorded in this province
+    pub fn places(&self) -> impl Iterator<Item = &'static Place> + '_ {
+        Place::all().filter(|place| place.province == self.code)
+    }
+}
+
+/// Provinces are deserialized from their code, the rest of the fields come from the registry
+#[cfg(feature = "serde")]
+impl<'de> serde::Deserialize<'de> for Province {
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        #[derive(serde::Deserialize)]
+        struct ProvinceCode {
+            code: String,
+        }
+
+        let ProvinceCode { code } = ProvinceCode::deserialize(deserializer)?;
+        Province::by_code(&code)
+            .ok_or_else(|| serde::de::Error::custom(format!("unknown province code {code}")))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_by_code() {
+        let province = Province::by_code("pd").unwrap();
+        assert_eq!(province.name, "Padova");
+        assert_eq!(province.region, Region::Veneto);
+        assert_eq!(province.region.name(), "Veneto");
+        assert!(province.active);
+
+        assert_eq!(Province::by_code("EE"), None);
+        assert_eq!(Province::by_code("PDX"), None);
+    }
+
+    #[test]
+    fn test_historic() {
+        let forli = Province::by_code("FO").unwrap();
+        assert!(!forli.active);
+        assert_eq!(forli.current().code, "FC");
+        assert_eq!(forli.current().name, "Forlì-Cesena");
+
+        let fiume = Province::by_code("FU").unwrap();
+        assert_eq!(fiume.region, Region::VeneziaGiulia);
+        assert_eq!(fiume.current(), fiume);
+
+        assert_eq!(Province::by_code("VS").unwrap().current().code, "SU");
+    }
+
+    #[test]
+    fn test_by_name() {
+        assert_eq!(Province::by_name("padova").unwrap().code, "PD");
+        assert_eq!(Province::by_name("Forli").unwrap().code, "FO");
+        assert_eq!(Province::by_name("Monza e Brianza").unwrap().code, "MB");
+        assert_eq!(Province::by_name("Pesaro e Urbino").unwrap().code, "PU");
+        assert_eq!(Province::by_name("Brianza"), None);
+    }
+
+    #[test]
+    fn test_every_place_has_a_province() {
+        let unknown = Place::all()
+            .filter(|place| place.province != "EE")
+            .find(|place| Province::by_code(place.province).is_none());
+        assert_eq!(unknown, None);
+    }
+
+    #[test]
+    fn test_region_provinces() {
+        let mut sardinia = Region::Sardegna
+            .provinces()
+            .map(|province| province.code)
+            .collect::<Vec<_>>();
+        sardinia.sort_unstable();
+        assert_eq!(
+            sardinia,
+            ["CA", "CI", "NU", "OG", "OR", "OT", "SS", "SU", "VS"]
+        );
+        assert_eq!(Province::by_code("OT").unwrap().places().count(), 26);
+    }
+}
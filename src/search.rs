@@ -0,0 +1,307 @@
+use std::cmp::Reverse;
+use std::sync::OnceLock;
+
+use crate::place::{LOWERCASE_WORDS, MAX_NAME_KEY_LEN};
+use crate::transliterate::transliterate_char;
+use crate::Place;
+
+/// Abbreviations of the saints that many municipalities are named after
+const ABBREVIATIONS: [(&str, &[&str]); 4] = [
+    ("s", &["san", "santa", "santo", "sant", "santi"]),
+    ("sta", &["santa"]),
+    ("sto", &["santo"]),
+    ("ss", &["santi"]),
+];
+
+/// The most readings of a query, after that abbreviations only get their first expansion.
+/// Every reading is matched against every place, and they multiply with each abbreviation
+const MAX_READINGS: usize = 25;
+
+/// Every place with its name as space separated words, without articles and prepositions
+static INDEX: OnceLock<Vec<(&'static Place, String)>> = OnceLock::new();
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SearchOptions {
+    /// Only return places in this province, `None` searches all of them
+    pub province: Option<String>,
+    pub limit: usize,
+    /// The maximum number of typos, it's lowered for short queries so that they don't match
+    /// everything: one typo from 3 letters and two from 6
+    pub max_distance: usize,
+    /// Also return places that don't exist anymore, after the active ones
+    pub include_inactive: bool,
+}
+
+impl Default for SearchOptions {
+    fn default() -> Self {
+        Self {
+            province: None,
+            limit: 10,
+            max_distance: 2,
+            include_inactive: true,
+        }
+    }
+}
+
+/// How the query matched the name of a place, from the best to the worst
+#[derive(Debug, PartialEq, Eq, PartialOrd, Ord, Clone, Copy)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "snake_case"))]
+pub enum MatchKind {
+    Exact,
+    Prefix,
+    /// The name, or its beginning, is `distance` typos away from the query
+    Fuzzy {
+        distance: usize,
+    },
+}
+
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct PlaceMatch {
+    #[cfg_attr(feature = "serde", serde(flatten))]
+    pub place: &'static Place,
+    #[cfg_attr(feature = "serde", serde(rename = "match"))]
+    pub kind: MatchKind,
+}
+
+impl Place {
+    /// Finds the places whose name matches the query, ranked by how well they match and then
+    /// active places first. Accents, punctuation, articles and prepositions are ignored and
+    /// the abbreviations S., Sta., Sto. and SS. are expanded, so "S. Giovanni in Fiore" and
+    /// "Reggio Emilia" find "san-giovanni-in-fiore" and "reggio-nell-emilia"
+    pub fn search(query: &str, options: &SearchOptions) -> Vec<PlaceMatch> {
+        let queries = expand_abbreviations(&normalize(query));
+        let Some(shortest) = queries.iter().map(String::len).min() else {
+            return Vec::new();
+        };
+        let max_distance = options.max_distance.min(shortest / 3);
+
+        let index = INDEX.get_or_init(|| {
+            Place::all()
+                .map(|place| (place, significant_words(place.name.split('-')).join(" ")))
+                .collect()
+        });
+
+        let mut matches = index
+            .iter()
+            .filter(|(place, _)| options.include_inactive || place.active)
+            .filter(|(place, _)| {
+                (options.province.as_deref())
+                    .is_none_or(|province| place.province.eq_ignore_ascii_case(province))
+            })
+            .filter_map(|(place, name)| {
+                let kind = queries
+                    .iter()
+                    .filter_map(|query| match_kind(query, name, max_distance))
+                    .min()?;
+                Some((PlaceMatch { place, kind }, name.len()))
+            })
+            .collect::<Vec<_>>();
+
+        matches.sort_by_key(|(m, len)| (m.kind, Reverse(m.place.active), *len, m.place.name));
+        matches
+            .into_iter()
+            .map(|(m, _)| m)
+            .take(options.limit)
+            .collect()
+    }
+}
+
+/// Lowercase ascii words separated by single spaces, without articles and prepositions
+pub(crate) fn normalize(query: &str) -> String {
+    let mut ascii = String::with_capacity(query.len());
+    for c in query.chars() {
+        if c.is_ascii_alphanumeric() {
+            ascii.push(c.to_ascii_lowercase());
+        } else if let Some(replacement) = transliterate_char(c) {
+            ascii.push_str(&replacement.to_ascii_lowercase());
+        } else {
+            ascii.push(' ');
+        }
+    }
+
+    let words = ascii.split(|c: char| !c.is_ascii_alphanumeric());
+    significant_words(words).join(" ")
+}
+
+fn significant_words<'a>(words: impl Iterator<Item = &'a str>) -> Vec<&'a str> {
+    words
+        .filter(|word| !word.is_empty() && !LOWERCASE_WORDS.contains(word))
+        .collect()
+}
+
+/// Returns the ways the abbreviations in the query can be read, at most [`MAX_READINGS`],
+/// or the query itself. Empty queries have no readings
+fn expand_abbreviations(query: &str) -> Vec<String> {
+    let mut readings = vec![String::new()];
+    for word in query.split(' ').filter(|word| !word.is_empty()) {
+        let word = [word];
+        let mut alternatives = ABBREVIATIONS
+            .iter()
+            .find(|(abbreviation, _)| *abbreviation == word[0])
+            .map_or(&word[..], |(_, expansions)| expansions);
+        if readings.len() * alternatives.len() > MAX_READINGS {
+            alternatives = &alternatives[..1];
+        }
+
+        readings = readings
+            .iter()
+            .flat_map(|reading| {
+                alternatives.iter().map(move |alternative| {
+                    if reading.is_empty() {
+                        alternative.to_string()
+                    } else {
+                        format!("{reading} {alternative}")
+                    }
+                })
+            })
+            .collect();
+    }
+
+    readings.retain(|reading| !reading.is_empty());
+    readings
+}
+
+fn match_kind(query: &str, name: &str, max_distance: usize) -> Option<MatchKind> {
+    if name == query {
+        return Some(MatchKind::Exact);
+    }
+    if name.starts_with(query) {
+        return Some(MatchKind::Prefix);
+    }
+
+    let distance = prefix_distance(query, name);
+    (distance <= max_distance).then_some(MatchKind::Fuzzy { distance })
+}
+
+/// The smallest edit distance between the query and any beginning of the name, so that
+/// partially typed names can match. Swapping two adjacent letters counts as a single typo.
+/// The rows live on the stack, names are never longer than the keys of the tables
+fn prefix_distance(query: &str, name: &str) -> usize {
+    let (query, name) = (query.as_bytes(), name.as_bytes());
+
+    let mut rows = [[0; MAX_NAME_KEY_LEN + 1]; 3];
+    let [mut before, mut previous, mut current] =
+        rows.each_mut().map(|row| &mut row[..=name.len()]);
+    for (j, cell) in previous.iter_mut().enumerate() {
+        *cell = j;
+    }
+    for (i, &q) in query.iter().enumerate() {
+        current[0] = i + 1;
+        for (j, &n) in name.iter().enumerate() {
+            let substitution = previous[j] + (q != n) as usize;
+            current[j + 1] = substitution.min(previous[j + 1] + 1).min(current[j] + 1);
+
+            if i > 0 && j > 0 && q == name[j - 1] && query[i - 1] == n {
+                current[j + 1] = current[j + 1].min(before[j - 1] + 1);
+            }
+        }
+        std::mem::swap(&mut before, &mut previous);
+        std::mem::swap(&mut previous, &mut current);
+    }
+
+    previous.iter().copied().min().unwrap_or(query.len())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn first_code(query: &str, options: &SearchOptions) -> Option<&'static str> {
+        Place::search(query, options).first().map(|m| m.place.code)
+    }
+
+    #[test]
+    fn test_normalize() {
+        assert_eq!(normalize("Reggio nell'Emilia"), "reggio emilia");
+        assert_eq!(normalize("  Forlì "), "forli");
+        assert_eq!(normalize("S.Giovanni in Fiore"), "s giovanni fiore");
+    }
+
+    #[test]
+    fn test_expand_abbreviations() {
+        assert_eq!(expand_abbreviations("sta maria"), vec!["santa maria"]);
+        assert_eq!(expand_abbreviations("s giovanni").len(), 5);
+        assert!(expand_abbreviations("").is_empty());
+
+        let readings = expand_abbreviations(&["s"; 200].join(" "));
+        assert_eq!(readings.len(), 25);
+        assert!(readings.iter().all(|reading| reading.ends_with("san san")));
+    }
+
+    #[test]
+    fn test_prefix_distance() {
+        assert_eq!(prefix_distance("padova", "padova"), 0);
+        assert_eq!(prefix_distance("padva", "padova"), 1);
+        assert_eq!(prefix_distance("pado", "padova"), 0);
+        assert_eq!(prefix_distance("pdaova", "padova"), 1);
+        assert_eq!(prefix_distance("pdova", "padova"), 1);
+        assert_eq!(prefix_distance("pavia", "padova"), 3);
+    }
+
+    #[test]
+    fn test_search_exact() {
+        let options = SearchOptions::default();
+        let matches = Place::search("Padova", &options);
+
+        assert_eq!(matches[0].place.code, "G224");
+        assert_eq!(matches[0].kind, MatchKind::Exact);
+    }
+
+    #[test]
+    fn test_search_connectors_and_abbreviations() {
+        let options = SearchOptions::default();
+        assert_eq!(first_code("Reggio Emilia", &options), Some("H223"));
+        assert_eq!(first_code("S. Giovanni in Fiore", &options), Some("H919"));
+    }
+
+    #[test]
+    fn test_search_typos() {
+        let options = SearchOptions::default();
+        let matches = Place::search("Padvoa", &options);
+
+        assert_eq!(matches[0].place.code, "G224");
+        assert!(matches!(matches[0].kind, MatchKind::Fuzzy { .. }));
+    }
+
+    #[test]
+    fn test_search_prefix_and_province() {
+        let options = SearchOptions {
+            province: Some("lc".into()),
+            ..Default::default()
+        };
+        let matches = Place::search("abbadia lar", &options);
+
+        assert_eq!(matches.len(), 1);
+        assert_eq!(matches[0].place.code, "A005");
+        assert_eq!(matches[0].kind, MatchKind::Prefix);
+    }
+
+    #[test]
+    fn test_search_ranking() {
+        let options = SearchOptions {
+            limit: 100,
+            ..Default::default()
+        };
+        let kinds = Place::search("san giovanni", &options)
+            .iter()
+            .map(|m| m.kind)
+            .collect::<Vec<_>>();
+
+        assert!(kinds.windows(2).all(|w| w[0] <= w[1]));
+        assert!(kinds.contains(&MatchKind::Prefix));
+        assert_eq!(kinds[0], MatchKind::Exact);
+    }
+
+    #[test]
+    fn test_search_inactive() {
+        let options = SearchOptions {
+            include_inactive: false,
+            ..Default::default()
+        };
+        assert!(Place::search("carano", &options)
+            .iter()
+            .all(|m| m.place.active));
+    }
+}
@@ -1,5 +1,7 @@
 use std::{ops::Deref, str::FromStr};
 
+use crate::transliterate::{transliterate_char, Transliteration};
+
 #[derive(Debug, PartialEq, Eq)]
 pub enum CFStringError {
     Empty,
@@ -11,30 +13,38 @@ impl std::error::Error for CFStringError {}
 impl std::fmt::Display for CFStringError {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         let message = match self {
-            CFStringError::Empty => "string must not be empty",
+            CFStringError::Empty => "string must contain at least one letter",
             CFStringError::NonAscii => "string must be valid ascii",
-            CFStringError::NonAlphabetic => "string must only have alphabetic or space characters",
+            CFStringError::NonAlphabetic => {
+                "string must only have alphabetic characters, spaces, apostrophes, hyphens or dots"
+            }
         };
         write!(f, "{message}")
     }
 }
 
+/// Characters allowed in names that are ignored when encoding them
+pub(crate) const SEPARATORS: [char; 4] = [' ', '\'', '-', '.'];
+
 #[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord)]
 pub struct CFString<S>(S);
 
 impl<S: AsRef<str>> CFString<S> {
+    /// Accepts ascii letters and separators like in "D'Angelo" or "Rossi-Bianchi",
+    /// the string must contain at least one letter
     pub fn new(s: S) -> Result<Self, CFStringError> {
-        if s.as_ref().is_empty() {
+        Self::verify_ascii_alphabetical_or_separator(s.as_ref())?;
+
+        if !s.as_ref().bytes().any(|b| b.is_ascii_alphabetic()) {
             return Err(CFStringError::Empty);
         }
-        Self::verify_ascii_alphabetical_or_space(s.as_ref())?;
 
         Ok(Self(s))
     }
 
-    fn verify_ascii_alphabetical_or_space(s: &str) -> Result<(), CFStringError> {
+    fn verify_ascii_alphabetical_or_separator(s: &str) -> Result<(), CFStringError> {
         for b in s.as_bytes() {
-            if *b == b' ' {
+            if SEPARATORS.contains(&(*b as char)) {
                 continue;
             }
             if !b.is_ascii() {
@@ -56,6 +66,32 @@ impl<S: AsRef<str>> CFString<S> {
     }
 }
 
+impl CFString<String> {
+    /// Replaces accented letters with their base letter and transliterates Cyrillic and
+    /// Greek letters before validating the string, returning every substitution that was made
+    pub fn transliterate(s: &str) -> Result<(Self, Vec<Transliteration>), CFStringError> {
+        let mut output = String::with_capacity(s.len());
+        let mut changes = Vec::new();
+
+        for (position, original) in s.chars().enumerate() {
+            if original.is_ascii() {
+                output.push(original);
+                continue;
+            }
+
+            let replacement = transliterate_char(original).ok_or(CFStringError::NonAscii)?;
+            output.push_str(&replacement);
+            changes.push(Transliteration {
+                position,
+                original,
+                replacement,
+            });
+        }
+
+        Ok((Self::new(output)?, changes))
+    }
+}
+
 impl<S> Deref for CFString<S> {
     type Target = S;
 
@@ -80,6 +116,21 @@ impl TryFrom<&str> for CFString<String> {
     }
 }
 
+#[cfg(feature = "serde")]
+impl<S: serde::Serialize> serde::Serialize for CFString<S> {
+    fn serialize<Ser: serde::Serializer>(&self, serializer: Ser) -> Result<Ser::Ok, Ser::Error> {
+        self.0.serialize(serializer)
+    }
+}
+
+#[cfg(feature = "serde")]
+impl<'de> serde::Deserialize<'de> for CFString<String> {
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let string = String::deserialize(deserializer)?;
+        Self::new(string).map_err(serde::de::Error::custom)
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -94,6 +145,26 @@ mod tests {
         assert_eq!(CFString::new(""), Err(CFStringError::Empty));
     }
 
+    #[test]
+    fn test_cfstring_only_separators() {
+        assert_eq!(CFString::new(" '-. "), Err(CFStringError::Empty));
+    }
+
+    #[test]
+    fn test_cfstring_separators() {
+        assert!(CFString::new("D'Angelo").is_ok());
+        assert!(CFString::new("Dell'Acqua").is_ok());
+        assert!(CFString::new("Rossi-Bianchi").is_ok());
+        assert!(CFString::new("O'Neil").is_ok());
+        assert!(CFString::new("S. Giovanni  in Fiore").is_ok());
+    }
+
+    #[test]
+    fn test_cfstring_other_punctuation() {
+        assert_eq!(CFString::new("Rossi,"), Err(CFStringError::NonAlphabetic));
+        assert_eq!(CFString::new("Rossi2"), Err(CFStringError::NonAlphabetic));
+    }
+
     #[test]
     fn test_cfstring_accents() {
         let err = Err(CFStringError::NonAscii);
@@ -109,4 +180,41 @@ mod tests {
         assert_eq!(CFString::new("ù"), err);
         assert_eq!(CFString::new("ú"), err);
     }
+
+    #[test]
+    fn test_cfstring_transliterate() {
+        let (string, changes) = CFString::transliterate("Nicolò").unwrap();
+        assert_eq!(string, CFString("Nicolo".to_owned()));
+        assert_eq!(
+            changes,
+            vec![Transliteration {
+                position: 5,
+                original: 'ò',
+                replacement: "o".to_owned(),
+            }]
+        );
+
+        let (string, changes) = CFString::transliterate("Strauß").unwrap();
+        assert_eq!(string, CFString("Strauss".to_owned()));
+        assert_eq!(changes.len(), 1);
+
+        let (string, _) = CFString::transliterate("Пётр").unwrap();
+        assert_eq!(string, CFString("Petr".to_owned()));
+
+        let (string, _) = CFString::transliterate("D’Angelo").unwrap();
+        assert_eq!(string, CFString("D'Angelo".to_owned()));
+    }
+
+    #[test]
+    fn test_cfstring_transliterate_ascii_untouched() {
+        let (string, changes) = CFString::transliterate("Mario").unwrap();
+        assert_eq!(string, CFString("Mario".to_owned()));
+        assert!(changes.is_empty());
+    }
+
+    #[test]
+    fn test_cfstring_transliterate_unknown() {
+        assert_eq!(CFString::transliterate("李"), Err(CFStringError::NonAscii));
+        assert_eq!(CFString::transliterate("ь"), Err(CFStringError::Empty));
+    }
 }
@@ -0,0 +1,137 @@
+/// A character that was replaced while transliterating a string
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub struct Transliteration {
+    /// Index of the character in the original string, counted in characters not bytes
+    pub position: usize,
+    pub original: char,
+    /// Can be empty for letters that have no sound of their own, like the Cyrillic soft sign
+    pub replacement: String,
+}
+
+/// Returns the ASCII replacement for a letter with diacritics, from the Cyrillic and Greek
+/// alphabets or for a typographic apostrophe. Upper case letters are replaced with upper case ones
+pub(crate) fn transliterate_char(c: char) -> Option<String> {
+    let lower = c.to_lowercase().next()?;
+    let replacement = replacement(lower)?;
+
+    if c.is_uppercase() {
+        Some(replacement.to_ascii_uppercase())
+    } else {
+        Some(replacement.to_owned())
+    }
+}
+
+fn replacement(c: char) -> Option<&'static str> {
+    let replacement = match c {
+        // Latin
+        'à' | 'á' | 'â' | 'ã' | 'ä' | 'å' | 'ā' | 'ă' | 'ą' => "a",
+        'æ' => "ae",
+        'ç' | 'ć' | 'ĉ' | 'ċ' | 'č' => "c",
+        'ď' | 'đ' | 'ð' => "d",
+        'è' | 'é' | 'ê' | 'ë' | 'ē' | 'ĕ' | 'ė' | 'ę' | 'ě' => "e",
+        'ĝ' | 'ğ' | 'ġ' | 'ģ' => "g",
+        'ĥ' | 'ħ' => "h",
+        'ì' | 'í' | 'î' | 'ï' | 'ĩ' | 'ī' | 'ĭ' | 'į' | 'ı' => "i",
+        'ĵ' => "j",
+        'ķ' => "k",
+        'ĺ' | 'ļ' | 'ľ' | 'ŀ' | 'ł' => "l",
+        'ñ' | 'ń' | 'ņ' | 'ň' => "n",
+        'ò' | 'ó' | 'ô' | 'õ' | 'ö' | 'ø' | 'ō' | 'ŏ' | 'ő' => "o",
+        'œ' => "oe",
+        'ŕ' | 'ŗ' | 'ř' => "r",
+        'ś' | 'ŝ' | 'ş' | 'š' | 'ș' => "s",
+        'ß' => "ss",
+        'ţ' | 'ť' | 'ŧ' | 'ț' => "t",
+        'þ' => "th",
+        'ù' | 'ú' | 'û' | 'ü' | 'ũ' | 'ū' | 'ŭ' | 'ů' | 'ű' | 'ų' => "u",
+        'ŵ' => "w",
+        'ý' | 'ÿ' | 'ŷ' => "y",
+        'ź' | 'ż' | 'ž' => "z",
+        '’' | 'ʼ' | '‘' | '´' => "'",
+        // Cyrillic
+        'а' => "a",
+        'б' => "b",
+        'в' => "v",
+        'г' | 'ґ' => "g",
+        'д' => "d",
+        'е' | 'ё' | 'э' => "e",
+        'є' => "ye",
+        'ж' => "zh",
+        'з' => "z",
+        'и' | 'і' => "i",
+        'ї' => "yi",
+        'й' | 'ы' => "y",
+        'к' => "k",
+        'л' => "l",
+        'м' => "m",
+        'н' => "n",
+        'о' => "o",
+        'п' => "p",
+        'р' => "r",
+        'с' => "s",
+        'т' => "t",
+        'у' => "u",
+        'ф' => "f",
+        'х' => "kh",
+        'ц' => "ts",
+        'ч' => "ch",
+        'ш' => "sh",
+        'щ' => "shch",
+        'ъ' | 'ь' => "",
+        'ю' => "yu",
+        'я' => "ya",
+        // Greek
+        'α' | 'ά' => "a",
+        'β' => "v",
+        'γ' => "g",
+        'δ' => "d",
+        'ε' | 'έ' => "e",
+        'ζ' => "z",
+        'η' | 'ή' | 'ι' | 'ί' | 'ϊ' | 'ΐ' => "i",
+        'θ' => "th",
+        'κ' => "k",
+        'λ' => "l",
+        'μ' => "m",
+        'ν' => "n",
+        'ξ' => "x",
+        'ο' | 'ό' | 'ω' | 'ώ' => "o",
+        'π' => "p",
+        'ρ' => "r",
+        'σ' | 'ς' => "s",
+        'τ' => "t",
+        'υ' | 'ύ' | 'ϋ' | 'ΰ' => "y",
+        'φ' => "f",
+        'χ' => "ch",
+        'ψ' => "ps",
+        _ => return None,
+    };
+
+    Some(replacement)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_transliterate_accents() {
+        assert_eq!(transliterate_char('ò').as_deref(), Some("o"));
+        assert_eq!(transliterate_char('È').as_deref(), Some("E"));
+        assert_eq!(transliterate_char('ß').as_deref(), Some("ss"));
+        assert_eq!(transliterate_char('Æ').as_deref(), Some("AE"));
+        assert_eq!(transliterate_char('Ñ').as_deref(), Some("N"));
+    }
+
+    #[test]
+    fn test_transliterate_alphabets() {
+        assert_eq!(transliterate_char('Ж').as_deref(), Some("ZH"));
+        assert_eq!(transliterate_char('ь').as_deref(), Some(""));
+        assert_eq!(transliterate_char('Ψ').as_deref(), Some("PS"));
+    }
+
+    #[test]
+    fn test_transliterate_unknown() {
+        assert_eq!(transliterate_char('a'), None);
+        assert_eq!(transliterate_char('中'), None);
+    }
+}
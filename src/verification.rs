@@ -0,0 +1,258 @@
+use crate::{BirthPlace, CodiceFiscale, Country, Gender, Place, Subject, ValidationError};
+
+/// The outcome of comparing one part of a codice fiscale with the data of a subject
+#[derive(Debug, PartialEq, Eq, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(tag = "result", rename_all = "snake_case"))]
+pub enum FieldCheck {
+    Match,
+    /// `expected` is what the subject's data encodes to, `found` what the code contains
+    Mismatch {
+        expected: String,
+        found: String,
+    },
+}
+
+impl FieldCheck {
+    fn compare(expected: String, found: &str) -> Self {
+        if expected == found {
+            Self::Match
+        } else {
+            Self::Mismatch {
+                expected,
+                found: found.to_owned(),
+            }
+        }
+    }
+
+    pub fn is_match(&self) -> bool {
+        matches!(self, Self::Match)
+    }
+}
+
+/// Field by field comparison between a codice fiscale and a subject
+#[derive(Debug, PartialEq, Eq, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Verification {
+    pub last_name: FieldCheck,
+    pub first_name: FieldCheck,
+    pub birth_date: FieldCheck,
+    pub gender: FieldCheck,
+    pub birth_place: FieldCheck,
+    pub checksum: FieldCheck,
+}
+
+impl Verification {
+    pub fn is_match(&self) -> bool {
+        [
+            &self.last_name,
+            &self.first_name,
+            &self.birth_date,
+            &self.gender,
+            &self.birth_place,
+            &self.checksum,
+        ]
+        .iter()
+        .all(|check| check.is_match())
+    }
+}
+
+impl CodiceFiscale {
+    /// Checks whether this codice fiscale belongs to the subject. Omocodes are normalized
+    /// before the comparison and the birth place matches the codes the place had on the
+    /// birth date, a [`BirthPlace::Belfiore`] code matches itself
+    pub fn verify(&self, subject: &Subject) -> Result<Verification, ValidationError> {
+        let original = self.get();
+        let normalized = self.normalize()?;
+        let code = normalized.get();
+
+        let last_name = CodiceFiscale::encode_last_name(subject.last_name.as_deref());
+        let first_name = CodiceFiscale::encode_first_name(subject.first_name.as_deref());
+
+        let date = CodiceFiscale::encode_birth_date(subject.birth_date, subject.gender);
+        let (found_date, found_gender) = split_date(&code[6..11]);
+        let (expected_date, expected_gender) = split_date(&date);
+
+        let checksum = CodiceFiscale::compute_checksum(&original[0..15])
+            .map_err(|_| ValidationError::IncorrectLength(16))?;
+
+        Ok(Verification {
+            last_name: FieldCheck::compare(last_name, &code[0..3]),
+            first_name: FieldCheck::compare(first_name, &code[3..6]),
+            birth_date: FieldCheck::compare(expected_date, &found_date),
+            gender: FieldCheck::compare(expected_gender, &found_gender),
+            birth_place: check_birth_place(subject, &code[11..15]),
+            checksum: FieldCheck::compare(checksum.to_string(), &original[15..16]),
+        })
+    }
+}
+
+/// Separates the gender from the encoded date by removing the 40 added to women's birth days
+fn split_date(date: &str) -> (String, String) {
+    let day = date[3..5].parse::<u8>().unwrap_or_default();
+    let (day, gender) = if day > 40 {
+        (day - 40, Gender::Female)
+    } else {
+        (day, Gender::Male)
+    };
+
+    (format!("{}{day:02}", &date[0..3]), format!("{gender:?}"))
+}
+
+fn check_birth_place(subject: &Subject, found: &str) -> FieldCheck {
+    let places = match &subject.birth_place {
+        BirthPlace::Italy {
+            municipality,
+            province: Some(province),
+        } => Place::by_name(municipality, province),
+        BirthPlace::Italy {
+            municipality,
+            province: None,
+        } => Place::by_municipality(municipality),
+        BirthPlace::Foreign { country } => match Country::by_iso(country) {
+            Some(country) => Place::by_code(country.code),
+            None => Place::by_name(country, "EE"),
+        },
+        // The code is encoded as is, whatever the birth date
+        BirthPlace::Belfiore { code } if code.get() == found => return FieldCheck::Match,
+        BirthPlace::Belfiore { code } => code.places(),
+    };
+
+    let valid = |p: &&Place| p.is_valid_on(subject.birth_date);
+    if places.iter().filter(valid).any(|p| p.code == found) {
+        return FieldCheck::Match;
+    }
+
+    let expected = Place::valid_on(places, subject.birth_date)
+        .or(places.first())
+        .map_or_else(String::new, |p| p.code.to_owned());
+
+    FieldCheck::Mismatch {
+        expected,
+        found: found.to_owned(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::str::FromStr;
+
+    use crate::NaiveDate;
+
+    use super::*;
+
+    fn subject() -> Subject {
+        Subject {
+            first_name: "Giancarlo".try_into().unwrap(),
+            last_name: "Galan".try_into().unwrap(),
+            birth_date: NaiveDate::from_ymd_opt(1956, 9, 10).unwrap(),
+            gender: Gender::Male,
+            birth_place: BirthPlace::Italy {
+                municipality: "Padova".try_into().unwrap(),
+                province: Some("PD".try_into().unwrap()),
+            },
+        }
+    }
+
+    #[test]
+    fn test_verify_match() {
+        let code = CodiceFiscale::from_str("GLNGCR56P10G224Q").unwrap();
+        assert!(code.verify(&subject()).unwrap().is_match());
+    }
+
+    #[test]
+    fn test_verify_omocode() {
+        let code = CodiceFiscale::from_str("GLNGCR56P10G224Q").unwrap();
+        for omocode in code.all_omocodes().unwrap() {
+            assert!(omocode.verify(&subject()).unwrap().is_match());
+        }
+    }
+
+    #[test]
+    fn test_verify_mismatches() {
+        let mut subject = subject();
+        subject.gender = Gender::Female;
+        subject.first_name = "Giovanni".try_into().unwrap();
+
+        let code = CodiceFiscale::from_str("GLNGCR56P10G224Q").unwrap();
+        let verification = code.verify(&subject).unwrap();
+
+        assert!(!verification.is_match());
+        assert!(verification.last_name.is_match());
+        assert!(verification.birth_date.is_match());
+        assert!(verification.birth_place.is_match());
+        assert_eq!(
+            verification.first_name,
+            FieldCheck::Mismatch {
+                expected: "GNN".into(),
+                found: "GCR".into()
+            }
+        );
+        assert_eq!(
+            verification.gender,
+            FieldCheck::Mismatch {
+                expected: "Female".into(),
+                found: "Male".into()
+            }
+        );
+    }
+
+    #[test]
+    fn test_verify_checksum() {
+        let code = CodiceFiscale::from_str("GLNGCR56P10G224A").unwrap();
+        let verification = code.verify(&subject()).unwrap();
+
+        assert_eq!(
+            verification.checksum,
+            FieldCheck::Mismatch {
+                expected: "Q".into(),
+                found: "A".into()
+            }
+        );
+    }
+
+    #[test]
+    fn test_verify_historic_place() {
+        let mut subject = subject();
+        subject.birth_place = BirthPlace::Italy {
+            municipality: "Longarone".try_into().unwrap(),
+            province: Some("BL".try_into().unwrap()),
+        };
+
+        let code = CodiceFiscale::from_str("GLNGCR56P10E672Q").unwrap();
+        assert!(code.verify(&subject).unwrap().birth_place.is_match());
+
+        // The new municipality was created in 2014
+        let code = CodiceFiscale::from_str("GLNGCR56P10M342Q").unwrap();
+        assert_eq!(
+            code.verify(&subject).unwrap().birth_place,
+            FieldCheck::Mismatch {
+                expected: "E672".into(),
+                found: "M342".into()
+            }
+        );
+        subject.birth_date = NaiveDate::from_ymd_opt(2020, 9, 10).unwrap();
+        assert!(code.verify(&subject).unwrap().birth_place.is_match());
+        subject.birth_date = NaiveDate::from_ymd_opt(1956, 9, 10).unwrap();
+
+        let code = CodiceFiscale::from_str("GLNGCR56P10G224Q").unwrap();
+        assert_eq!(
+            code.verify(&subject).unwrap().birth_place,
+            FieldCheck::Mismatch {
+                expected: "E672".into(),
+                found: "G224".into()
+            }
+        );
+    }
+
+    #[test]
+    fn test_verify_foreign() {
+        let mut subject = subject();
+        subject.birth_place = BirthPlace::Foreign {
+            country: "US".try_into().unwrap(),
+        };
+
+        let code = CodiceFiscale::from_str("GLNGCR56P10Z404Q").unwrap();
+        assert!(code.verify(&subject).unwrap().birth_place.is_match());
+    }
+}
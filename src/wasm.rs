@@ -0,0 +1,404 @@
+//! Bindings for JavaScript, build them with
+//! `cargo rustc --lib --target wasm32-unknown-unknown --features wasm --crate-type cdylib`
+//! and generate the JavaScript glue with the `wasm-bindgen` command
+
+use chrono::{DateTime, NaiveDate};
+use wasm_bindgen::prelude::*;
+
+use crate::{
+    BirthPlace, CFString, CFStringError, CenturyPolicy, CodiceFiscale, DecodedBirthPlace, Gender,
+    GenerationError, Place, PlaceStatus, SearchOptions, Subject, ValidationError,
+};
+
+#[wasm_bindgen]
+extern "C" {
+    #[wasm_bindgen(js_namespace = Date)]
+    fn now() -> f64;
+}
+
+/// Thrown by every function that can fail. `kind` is a snake case identifier that's safe to
+/// match on, like `invalid_checksum`, `message` is the description of the Rust error
+#[wasm_bindgen(getter_with_clone)]
+#[derive(Debug, Clone)]
+pub struct CodiceFiscaleError {
+    pub kind: String,
+    pub message: String,
+    /// The 0 based position of the wrong character, only for `invalid_character` errors
+    pub position: Option<usize>,
+    /// The provinces of the municipalities with the given name, only for
+    /// `ambiguous_municipality` errors
+    pub provinces: Vec<String>,
+}
+
+impl CodiceFiscaleError {
+    fn new(kind: &str, message: impl ToString) -> Self {
+        Self {
+            kind: kind.to_owned(),
+            message: message.to_string(),
+            position: None,
+            provinces: Vec::new(),
+        }
+    }
+}
+
+impl From<ValidationError> for CodiceFiscaleError {
+    fn from(error: ValidationError) -> Self {
+        let kind = match error {
+            ValidationError::IncorrectLength(_) => "incorrect_length",
+            ValidationError::InvalidCharacter { .. } => "invalid_character",
+            ValidationError::InvalidChecksum => "invalid_checksum",
+            ValidationError::InvalidDate => "invalid_date",
+            ValidationError::InvalidDay => "invalid_day",
+            ValidationError::InvalidMonthLetter => "invalid_month_letter",
+            ValidationError::InvalidOmocodeLetter => "invalid_omocode_letter",
+            ValidationError::NonAlphanumeric => "non_alphanumeric",
+            ValidationError::NonNumeric => "non_numeric",
+            ValidationError::NotANaturalPerson => "not_a_natural_person",
+            ValidationError::UnknownPlaceCode => "unknown_place_code",
+        };
+
+        let mut output = Self::new(kind, &error);
+        if let ValidationError::InvalidCharacter { position, .. } = error {
+            output.position = Some(position);
+        }
+        output
+    }
+}
+
+impl From<GenerationError> for CodiceFiscaleError {
+    fn from(error: GenerationError) -> Self {
+        let kind = match error {
+            GenerationError::BelfioreCodeNotFound => "belfiore_code_not_found",
+            GenerationError::IncorrectChecksumInputLength
+            | GenerationError::IncorrectPartitaIvaChecksumInputLength => {
+                "incorrect_checksum_input_length"
+            }
+            GenerationError::NonNumericChecksumInput => "non_numeric_checksum_input",
+            GenerationError::NonAlphanumericChecksumInput => "non_alphanumeric",
+            GenerationError::NumberOutOfRange => "number_out_of_range",
+            GenerationError::AmbiguousMunicipality(_) => "ambiguous_municipality",
+            GenerationError::UnknownProvince => "unknown_province",
+        };
+
+        let mut output = Self::new(kind, &error);
+        if let GenerationError::AmbiguousMunicipality(provinces) = error {
+            output.provinces = provinces.into_iter().map(str::to_owned).collect();
+        }
+        output
+    }
+}
+
+impl From<CFStringError> for CodiceFiscaleError {
+    fn from(error: CFStringError) -> Self {
+        let kind = match error {
+            CFStringError::Empty => "empty_string",
+            CFStringError::NonAscii => "non_ascii_string",
+            CFStringError::NonAlphabetic => "non_alphabetic_string",
+        };
+        Self::new(kind, error)
+    }
+}
+
+/// The data contained in a codice fiscale, see [`crate::DecodedData`]
+#[wasm_bindgen(getter_with_clone)]
+#[derive(Debug, Clone)]
+pub struct DecodedCode {
+    #[wasm_bindgen(js_name = lastName)]
+    pub last_name: String,
+    #[wasm_bindgen(js_name = firstName)]
+    pub first_name: String,
+    /// In the YYYY-MM-DD format
+    #[wasm_bindgen(js_name = birthDate)]
+    pub birth_date: String,
+    /// `male` or `female`
+    pub gender: String,
+    /// The municipality or the country
+    #[wasm_bindgen(js_name = birthPlace)]
+    pub birth_place: String,
+    /// `None` for people born abroad
+    pub province: Option<String>,
+    /// Like "Veneto", `None` for people born abroad
+    pub region: Option<String>,
+    #[wasm_bindgen(js_name = belfioreCode)]
+    pub belfiore_code: String,
+    /// `active`, `inactive` or `foreign`
+    #[wasm_bindgen(js_name = placeStatus)]
+    pub place_status: String,
+    #[wasm_bindgen(js_name = isOmocode)]
+    pub is_omocode: bool,
+}
+
+#[wasm_bindgen(getter_with_clone)]
+#[derive(Debug, Clone)]
+pub struct PlaceMatch {
+    pub code: String,
+    /// The display name, like "Abbadia Lariana"
+    pub name: String,
+    pub province: String,
+    pub active: bool,
+}
+
+/// Encodes the codice fiscale of someone born in Italy, the birth date is in the
+/// YYYY-MM-DD format and the gender is `M` or `F`. The province can be left out when no other
+/// municipality has ever had the same name
+#[wasm_bindgen]
+pub fn encode(
+    #[wasm_bindgen(js_name = firstName)] first_name: String,
+    #[wasm_bindgen(js_name = lastName)] last_name: String,
+    #[wasm_bindgen(js_name = birthDate)] birth_date: &str,
+    gender: &str,
+    municipality: String,
+    province: Option<String>,
+) -> Result<String, CodiceFiscaleError> {
+    let birth_place = BirthPlace::Italy {
+        municipality: CFString::new(municipality)?,
+        province: province.map(CFString::new).transpose()?,
+    };
+    encode_subject(first_name, last_name, birth_date, gender, birth_place)
+}
+
+/// Encodes the codice fiscale of someone born abroad, the country is its Italian or English
+/// name or its ISO code
+#[wasm_bindgen(js_name = encodeForeign)]
+pub fn encode_foreign(
+    #[wasm_bindgen(js_name = firstName)] first_name: String,
+    #[wasm_bindgen(js_name = lastName)] last_name: String,
+    #[wasm_bindgen(js_name = birthDate)] birth_date: &str,
+    gender: &str,
+    country: String,
+) -> Result<String, CodiceFiscaleError> {
+    let birth_place = BirthPlace::Foreign {
+        country: CFString::new(country)?,
+    };
+    encode_subject(first_name, last_name, birth_date, gender, birth_place)
+}
+
+fn encode_subject(
+    first_name: String,
+    last_name: String,
+    birth_date: &str,
+    gender: &str,
+    birth_place: BirthPlace,
+) -> Result<String, CodiceFiscaleError> {
+    let gender = match gender.to_ascii_uppercase().as_str() {
+        "M" | "MALE" => Gender::Male,
+        "F" | "FEMALE" => Gender::Female,
+        _ => {
+            return Err(CodiceFiscaleError::new(
+                "invalid_gender",
+                "gender must be M or F",
+            ))
+        }
+    };
+
+    let subject = Subject::new(
+        CFString::new(first_name)?,
+        CFString::new(last_name)?,
+        parse_date(birth_date)?,
+        gender,
+        birth_place,
+    )?;
+
+    Ok(CodiceFiscale::encode(&subject)?.to_string())
+}
+
+/// Checks every character, the date and the check character, throws if the code is invalid
+#[wasm_bindgen]
+pub fn validate(code: &str) -> Result<(), CodiceFiscaleError> {
+    CodiceFiscale::parse_strict(code)?;
+    Ok(())
+}
+
+/// Returns the codes one typo away from an invalid code, the most plausible first. Birth dates
+/// after the reference date, today if it's not given, are considered less plausible
+#[wasm_bindgen(js_name = suggestCorrections)]
+pub fn suggest_corrections(
+    code: &str,
+    #[wasm_bindgen(js_name = referenceDate)] reference_date: Option<String>,
+) -> Result<Vec<String>, CodiceFiscaleError> {
+    let reference = match reference_date {
+        Some(date) => parse_date(&date)?,
+        None => today(),
+    };
+    let code = code.parse::<CodiceFiscale>()?;
+    Ok(code
+        .suggest_corrections_on(reference)
+        .iter()
+        .map(ToString::to_string)
+        .collect())
+}
+
+/// The check character of the first 15 characters of a code, see
+/// [`CodiceFiscale::compute_checksum`]
+#[wasm_bindgen(js_name = computeChecksum)]
+pub fn compute_checksum(partial: &str) -> Result<String, CodiceFiscaleError> {
+    Ok(CodiceFiscale::compute_checksum(partial)?.to_string())
+}
+
+/// Decodes a codice fiscale, the century of the birth date is the most recent one that's not
+/// after the reference date, today if it's not given
+#[wasm_bindgen]
+pub fn decode(
+    code: &str,
+    #[wasm_bindgen(js_name = referenceDate)] reference_date: Option<String>,
+) -> Result<DecodedCode, CodiceFiscaleError> {
+    let reference = match reference_date {
+        Some(date) => parse_date(&date)?,
+        None => today(),
+    };
+    let decoded =
+        CodiceFiscale::parse_strict(code)?.decode_with(reference, CenturyPolicy::PreferPast)?;
+
+    let is_omocode = decoded.is_omocode();
+    let region = decoded
+        .birth_place
+        .province()
+        .map(|province| province.region.name().to_owned());
+    let (birth_place, province) = match decoded.birth_place {
+        DecodedBirthPlace::Italy {
+            municipality,
+            province,
+        } => (municipality, Some(province)),
+        DecodedBirthPlace::Foreign(country) => (country.display_name(), None),
+    };
+    let place_status = match decoded.place_status {
+        PlaceStatus::Active => "active",
+        PlaceStatus::Inactive => "inactive",
+        PlaceStatus::Foreign => "foreign",
+    };
+
+    Ok(DecodedCode {
+        is_omocode,
+        last_name: decoded.last_name,
+        first_name: decoded.first_name,
+        birth_date: decoded.birth_date.to_string(),
+        gender: format!("{:?}", decoded.gender).to_ascii_lowercase(),
+        birth_place,
+        province,
+        region,
+        belfiore_code: decoded.belfiore_code,
+        place_status: place_status.to_owned(),
+    })
+}
+
+/// Places whose name matches the query even with typos or abbreviations, the best matches
+/// first, see [`Place::search`]
+#[wasm_bindgen(js_name = searchPlaces)]
+pub fn search_places(query: &str, province: Option<String>, limit: usize) -> Vec<PlaceMatch> {
+    let options = SearchOptions {
+        province,
+        limit,
+        ..Default::default()
+    };
+    Place::search(query, &options)
+        .into_iter()
+        .map(|m| PlaceMatch {
+            code: m.place.code.to_owned(),
+            name: m.place.display_name(),
+            province: m.place.province.to_owned(),
+            active: m.place.active,
+        })
+        .collect()
+}
+
+fn parse_date(date: &str) -> Result<NaiveDate, CodiceFiscaleError> {
+    date.parse()
+        .map_err(|_| CodiceFiscaleError::new("invalid_input_date", "dates must be YYYY-MM-DD"))
+}
+
+/// The current date from the JavaScript clock, `Utc::now` isn't available on wasm32
+fn today() -> NaiveDate {
+    DateTime::from_timestamp_millis(now() as i64)
+        .expect("the JavaScript clock is in range")
+        .date_naive()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_encode() {
+        let code = encode(
+            "Giancarlo".into(),
+            "Galan".into(),
+            "1956-09-10",
+            "m",
+            "Padova".into(),
+            Some("PD".into()),
+        );
+        assert_eq!(code.unwrap(), "GLNGCR56P10G224Q");
+
+        let error = encode(
+            "Giancarlo".into(),
+            "Galan".into(),
+            "1956-09-10",
+            "m",
+            "Peglio".into(),
+            None,
+        )
+        .unwrap_err();
+        assert_eq!(error.kind, "ambiguous_municipality");
+        assert_eq!(error.provinces, ["CO", "PS", "PU"]);
+
+        let error = encode_foreign(
+            "Maria".into(),
+            "Rossi".into(),
+            "1970-01-01",
+            "x",
+            "DE".into(),
+        )
+        .unwrap_err();
+        assert_eq!(error.kind, "invalid_gender");
+    }
+
+    #[test]
+    fn test_validate_errors() {
+        assert!(validate("GLNGCR56P10G224Q").is_ok());
+
+        let error = validate("GLN1CR56P10G224Q").unwrap_err();
+        assert_eq!(error.kind, "invalid_character");
+        assert_eq!(error.position, Some(3));
+
+        let error = validate("GLNGCR56P10G224A").unwrap_err();
+        assert_eq!(error.kind, "invalid_checksum");
+        assert_eq!(error.message, ValidationError::InvalidChecksum.to_string());
+    }
+
+    #[test]
+    fn test_suggest_corrections() {
+        let suggestions =
+            suggest_corrections("RSSMRA70A41F205Y", Some("2024-06-01".into())).unwrap();
+        assert_eq!(suggestions[0], "RSSMRA70A41F205Z");
+
+        let error = suggest_corrections("RSSMRA70A41F205Y", Some("01/06/2024".into()));
+        assert_eq!(error.unwrap_err().kind, "invalid_input_date");
+    }
+
+    #[test]
+    fn test_compute_checksum() {
+        assert_eq!(compute_checksum("GLNGCR56P10G224").unwrap(), "Q");
+        assert_eq!(
+            compute_checksum("GLNGCR56P10G22-").unwrap_err().kind,
+            "non_alphanumeric"
+        );
+    }
+
+    #[test]
+    fn test_decode() {
+        let decoded = decode("GLNGCR56P10G224Q", Some("2024-01-01".into())).unwrap();
+        assert_eq!(decoded.birth_date, "1956-09-10");
+        assert_eq!(decoded.gender, "male");
+        assert_eq!(decoded.birth_place, "Padova");
+        assert_eq!(decoded.province.as_deref(), Some("PD"));
+        assert_eq!(decoded.region.as_deref(), Some("Veneto"));
+        assert_eq!(decoded.place_status, "active");
+    }
+
+    #[test]
+    fn test_search_places() {
+        let places = search_places("abbadia lar", Some("LC".into()), 5);
+        assert_eq!(places.len(), 1);
+        assert_eq!(places[0].name, "Abbadia Lariana");
+    }
+}